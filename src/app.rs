@@ -0,0 +1,689 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveTime};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::audio::{self, AudioPlayer, PlaybackOptions};
+use crate::config::{self, Config, FallbackSound, Schedule};
+use crate::event_loop::EventHandler;
+use crate::history::EventHistory;
+use crate::mqtt::MqttBridge;
+use crate::ipc::{ControlCommand, ControlResponse};
+use crate::state::{AppState, StateStore};
+use crate::scheduler::{CronScheduler, RetryPolicy, ScheduleEvent, ScheduleOutcome};
+use crate::tray::{SystemTray, TrayMenuEvent, TrayStatus};
+use crate::watcher::SoundWatcher;
+use crate::webhook::WebhookPublisher;
+
+/// 実行中のアプリケーションの状態
+pub struct App {
+    pub config_path: PathBuf,
+    pub log_dir: PathBuf,
+    pub config: Config,
+    pub audio_player: Arc<AudioPlayer>,
+    pub scheduler: CronScheduler,
+    pub history: Option<EventHistory>,
+    /// スケジュールイベントの送信先（HTTPクライアントを作成できなかった場合は None）
+    pub webhooks: Option<WebhookPublisher>,
+    /// MQTT ブローカーとの接続（設定がない場合は None）
+    pub mqtt: Option<MqttBridge>,
+    /// スヌーズ・スキップなどの保存先
+    pub state: Option<StateStore>,
+    /// 設定ファイルの再読み込み要求（エディタ終了時など）
+    pub reload_tx: mpsc::UnboundedSender<()>,
+    /// 音声ファイルの変更の監視（監視を開始できなかった場合は None）
+    pub sound_watcher: Option<SoundWatcher>,
+    /// タスクトレイ（ヘッドレスモードでは None）
+    pub tray: Option<SystemTray>,
+    /// トレイのアイコン・ツールチップに表示する状態
+    pub tray_status: TrayStatus,
+}
+
+impl App {
+    /// 有効なスケジュールの音声ファイルを事前にロード
+    ///
+    /// ロードに失敗したスケジュールのIDとエラー内容を返す
+    pub fn preload_sounds(audio_player: &AudioPlayer, config: &Config) -> BTreeMap<String, String> {
+        let mut errors = BTreeMap::new();
+        for schedule in &config.schedules {
+            if schedule.enabled && schedule.delivery.plays_sound() {
+                info!(schedule_id = %schedule.id, file = %schedule.sound_path().display(), "Preloading audio file");
+                if let Err(e) = audio_player.preload_sound(schedule.sound_path(), &PlaybackOptions::for_schedule(schedule)) {
+                    error!(schedule_id = %schedule.id, file = %schedule.file, error = %format!("{:#}", e), "Failed to preload sound file");
+                    errors.insert(schedule.id.clone(), format!("{:#}", e));
+                } else if let Some(info) = audio_player.sound_info(schedule.sound_path())
+                    && let Some(message) = audio::check_fire_interval(schedule, &info)
+                {
+                    warn!(schedule_id = %schedule.id, "{}", message);
+                }
+            }
+        }
+        // 代替音声は失敗してもスケジュールのエラーにはしない
+        for file in Self::fallback_files(config) {
+            info!(file = %file.display(), "Preloading fallback audio file");
+            if let Err(e) = audio_player.preload_sound(file, &PlaybackOptions::default()) {
+                warn!(file = %file.display(), error = %format!("{:#}", e), "Failed to preload fallback sound file");
+            }
+        }
+        errors
+    }
+
+    /// 事前ロード・監視の対象となる音声ファイル（有効な音声スケジュールのもの）
+    fn sound_schedules(config: &Config) -> impl Iterator<Item = &Schedule> {
+        config.schedules
+            .iter()
+            .filter(|s| s.enabled && s.delivery.plays_sound() && !s.file.is_empty())
+    }
+
+    /// 有効な音声スケジュールが代替音声として参照している音声ファイル（いずれかのスケジュールの `file` であるものは除く）
+    fn fallback_files(config: &Config) -> BTreeSet<&Path> {
+        let primary: BTreeSet<&Path> = Self::sound_schedules(config).map(Schedule::sound_path).collect();
+        Self::sound_schedules(config)
+            .flat_map(|schedule| &schedule.resolved_fallback)
+            .filter_map(|fallback| match fallback {
+                FallbackSound::File(path) => Some(path.as_path()),
+                FallbackSound::Builtin(_) => None,
+            })
+            .filter(|path| !primary.contains(path))
+            .collect()
+    }
+
+    /// 現在の設定で参照している音声ファイル（代替音声を含む）を監視対象にする
+    pub fn watch_sound_files(&mut self) {
+        if let Some(watcher) = self.sound_watcher.as_mut() {
+            let fallback_files = Self::fallback_files(&self.config);
+            watcher.set_files(Self::sound_schedules(&self.config).map(Schedule::sound_path).chain(fallback_files));
+        }
+    }
+
+    /// 変更された音声ファイルを事前ロードし直す
+    ///
+    /// 削除されたファイルはキャッシュから外し、そのファイルを使うスケジュールをエラー状態にする
+    pub fn handle_sound_changes(&mut self, files: Vec<PathBuf>) {
+        let fallback_files: BTreeSet<PathBuf> = Self::fallback_files(&self.config).into_iter().map(Path::to_path_buf).collect();
+        for file in &files {
+            if fallback_files.contains(file) {
+                if file.exists() {
+                    info!(file = %file.display(), "Fallback audio file changed, reloading");
+                    if let Err(e) = self.audio_player.preload_sound(file, &PlaybackOptions::default()) {
+                        warn!(file = %file.display(), error = %format!("{:#}", e), "Failed to reload fallback sound file");
+                    }
+                } else {
+                    warn!(file = %file.display(), "Fallback audio file was removed");
+                    self.audio_player.forget_sound(file);
+                }
+                continue;
+            }
+
+            let schedules: Vec<&Schedule> = Self::sound_schedules(&self.config).filter(|s| s.sound_path() == file).collect();
+
+            if !file.exists() {
+                error!(file = %file.display(), "Audio file was removed");
+                self.audio_player.forget_sound(file);
+                for schedule in schedules {
+                    self.tray_status.errors.insert(schedule.id.clone(), format!("Audio file not found: {}", file.display()));
+                }
+                continue;
+            }
+
+            info!(file = %file.display(), "Audio file changed, reloading");
+            for schedule in schedules {
+                match self.audio_player.preload_sound(file, &PlaybackOptions::for_schedule(schedule)) {
+                    Ok(()) => {
+                        self.tray_status.errors.remove(&schedule.id);
+                    }
+                    Err(e) => {
+                        error!(schedule_id = %schedule.id, file = %file.display(), error = %format!("{:#}", e), "Failed to reload sound file");
+                        self.tray_status.errors.insert(schedule.id.clone(), format!("{:#}", e));
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = self.update_tray_menu() {
+            warn!("Failed to update tray menu: {}", e);
+        }
+        self.refresh_tray_status();
+    }
+
+    /// スケジュールイベントを記録
+    pub fn record_event(&mut self, event: &ScheduleEvent) {
+        let schedule_id = event.schedule_id.as_str();
+        let planned_at = event.planned_at.to_rfc3339();
+        let triggered_at = event.triggered_at.to_rfc3339();
+        // 予定時刻からの遅れ（予定時刻の少し前に実行した場合は負）
+        let latency_ms = event.triggered_at.signed_duration_since(event.planned_at).num_milliseconds();
+        let outcome = event.outcome.kind();
+        match &event.outcome {
+            ScheduleOutcome::Fired => {
+                info!(schedule_id, planned_at, triggered_at, latency_ms, outcome, "Schedule executed");
+            }
+            ScheduleOutcome::Succeeded { fallback } => {
+                info!(schedule_id, planned_at, triggered_at, latency_ms, outcome, fallback = fallback.as_deref(), "Schedule completed");
+                self.tray_status.errors.remove(&event.schedule_id);
+            }
+            ScheduleOutcome::Failed { error } => {
+                warn!(schedule_id, planned_at, triggered_at, latency_ms, outcome, error = error.as_str(), "Schedule failed");
+                self.tray_status.errors.insert(event.schedule_id.clone(), error.clone());
+            }
+            ScheduleOutcome::Skipped { reason } => {
+                info!(schedule_id, planned_at, triggered_at, latency_ms, outcome, reason = reason.as_str(), "Schedule skipped");
+            }
+        }
+
+        if let Some(history) = self.history.as_mut()
+            && let Err(e) = history.record(event)
+        {
+            warn!("Failed to record schedule event to history: {}", e);
+        }
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.publish(event);
+        }
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish_event(event);
+        }
+    }
+
+    /// スヌーズ・スキップ予定をトレイに反映し、変化があれば状態ファイルに保存
+    pub fn sync_pending_fires(&mut self) {
+        if let Some(tray) = self.tray.as_mut()
+            && let Err(e) = tray.set_pending_fires(&self.scheduler.pending_fires())
+        {
+            warn!("Failed to update tray menu: {}", e);
+        }
+        self.save_state();
+    }
+
+    /// スヌーズ・スキップ・一時停止・ミュートの状態を保存（変化がなければ何もしない）
+    fn save_state(&mut self) {
+        let current = AppState {
+            pending: self.scheduler.pending_fires(),
+            paused_until: self.scheduler.paused_until(),
+            muted: self.audio_player.is_muted(),
+        };
+        if let Some(state) = self.state.as_mut()
+            && let Err(e) = state.save(&current)
+        {
+            warn!("Failed to save state file: {:#}", e);
+        }
+    }
+
+    /// 次回実行予定と静音時間帯をトレイのアイコン・ツールチップに反映
+    pub fn refresh_tray_status(&mut self) {
+        self.tray_status.next_fire = self.scheduler.next_fire();
+        self.tray_status.paused_until = self.scheduler.paused_until();
+        self.tray_status.muted = self.audio_player.is_muted();
+        self.tray_status.volume = self.audio_player.global_volume();
+        self.tray_status.quiet_hours = self.config.behavior.quiet_hours
+            .as_ref()
+            .is_some_and(|q| q.contains(Local::now().time()));
+        if let Some(tray) = self.tray.as_mut() {
+            tray.set_status(&self.tray_status);
+        }
+        // 一時停止の自動再開もここで保存される
+        self.save_state();
+    }
+
+    /// 最初の有効な音声スケジュールの音声を現在の音量で再生
+    fn play_test_sound(&self) {
+        let mut candidates = self.config.schedules
+            .iter()
+            .filter(|s| s.delivery.plays_sound() && !s.file.is_empty());
+        let file = candidates
+            .clone()
+            .find(|s| s.enabled)
+            .or_else(|| candidates.next())
+            .map(|s| s.sound_path().to_path_buf());
+        let Some(file) = file else {
+            warn!("No schedule with an audio file to test volume");
+            return;
+        };
+
+        info!(file = %file.display(), volume = self.audio_player.global_volume(), "Playing test sound");
+        self.play_file(file);
+    }
+
+    /// 音声ファイルをバックグラウンドで再生
+    fn play_file(&self, file: PathBuf) {
+        let audio_player = self.audio_player.clone();
+        tokio::spawn(async move {
+            if let Err(e) = audio_player.play_sound(&file, &PlaybackOptions::default()).await {
+                error!(file = %file.display(), error = %format!("{:#}", e), "Failed to play sound");
+            }
+        });
+    }
+
+    /// 次に来る指定時刻（今日のその時刻を過ぎていれば翌日）
+    fn next_occurrence(time: NaiveTime) -> Option<DateTime<Local>> {
+        let now = Local::now();
+        let today = now.date_naive().and_time(time).and_local_timezone(Local).earliest()?;
+        if today > now {
+            Some(today)
+        } else {
+            (now.date_naive() + chrono::Days::new(1))
+                .and_time(time)
+                .and_local_timezone(Local)
+                .earliest()
+        }
+    }
+
+    /// 設定ファイルを再読み込みしてスケジュールとトレイメニューに反映
+    ///
+    /// 読み込みに失敗した場合は現在の設定を維持する
+    pub fn reload_config(&mut self) -> Result<()> {
+        info!("Reloading config file: {:?}", self.config_path);
+
+        let new_config = Config::load_from_file(&self.config_path)
+            .context("Failed to reload config file, keeping current settings")?;
+
+        let report = new_config.validate();
+        for issue in &report.issues {
+            warn!("Config issue: {:?} {:?} {} ({:?})", issue.severity, issue.schedule_id, issue.message, issue.source);
+        }
+
+        self.audio_player.set_stream_threshold(&new_config.audio);
+        self.tray_status.errors = Self::preload_sounds(&self.audio_player, &new_config);
+        if new_config.audio.global_volume != self.config.audio.global_volume {
+            self.audio_player.set_global_volume(new_config.audio.global_volume);
+        }
+        self.scheduler.replace_schedules(new_config.schedules.clone());
+        self.scheduler.set_quiet_hours(new_config.behavior.quiet_hours.clone());
+        self.scheduler.set_retry_policy(RetryPolicy::from_config(&new_config.behavior));
+        if let Some(webhooks) = self.webhooks.as_mut() {
+            webhooks.set_hooks(&new_config.webhooks);
+        }
+        if new_config.http != self.config.http {
+            warn!("HTTP endpoint settings are applied on restart");
+        }
+        if new_config.mqtt != self.config.mqtt {
+            warn!("MQTT settings are applied on restart");
+        }
+
+        if new_config.ui.language != self.config.ui.language {
+            crate::i18n::init(new_config.ui.language.as_deref(), new_config.locales_dir().as_deref());
+        }
+
+        // ログ・音声デバイスの設定は再起動時に反映される（音量は変更された場合のみ反映）
+        self.config = new_config;
+        info!("Config reloaded: {} schedule(s)", self.config.schedules.len());
+        self.watch_sound_files();
+
+        if let Some(tray) = self.tray.as_mut() {
+            if let Err(e) = tray.set_sound_info(self.audio_player.sound_infos()) {
+                warn!("Failed to rebuild tray menu after reload: {}", e);
+            }
+            if let Err(e) = tray.set_schedules(&self.config.schedules) {
+                warn!("Failed to rebuild tray menu after reload: {}", e);
+            }
+            if let Err(e) = tray.set_pause_times(self.config.ui.pause_until_times()) {
+                warn!("Failed to rebuild tray menu after reload: {}", e);
+            }
+        }
+        self.refresh_tray_status();
+        Ok(())
+    }
+
+    /// スケジュールの有効/無効を切り替えて設定ファイルに保存
+    fn toggle_schedule(&mut self, schedule_id: &str) -> Result<()> {
+        let enabled = !self.config.schedules
+            .iter()
+            .find(|s| s.id == schedule_id)
+            .map(|s| s.enabled)
+            .unwrap_or(false);
+        self.set_schedule_enabled(schedule_id, enabled)
+    }
+
+    /// スケジュールの有効/無効を設定して設定ファイルに保存
+    fn set_schedule_enabled(&mut self, schedule_id: &str, enabled: bool) -> Result<()> {
+        self.config.set_schedule_enabled(schedule_id, enabled)?;
+        self.config.save_to_file(&self.config_path)?;
+        info!("Schedule '{}' {}", schedule_id, if enabled { "enabled" } else { "disabled" });
+
+        self.tray_status.errors.remove(schedule_id);
+        if enabled
+            && let Some(schedule) = self.config.schedules.iter().find(|s| s.id == schedule_id)
+            && schedule.delivery.plays_sound()
+            && let Err(e) = self.audio_player.preload_sound(schedule.sound_path(), &PlaybackOptions::for_schedule(schedule))
+        {
+            error!(schedule_id = %schedule.id, file = %schedule.file, error = %format!("{:#}", e), "Failed to preload sound file");
+            self.tray_status.errors.insert(schedule_id.to_string(), format!("{:#}", e));
+        }
+
+        self.scheduler.replace_schedules(self.config.schedules.clone());
+        self.watch_sound_files();
+        self.update_tray_menu()
+    }
+
+    /// トレイメニューを現在のスケジュールで再構築（次回実行時刻の表示を更新）
+    pub fn update_tray_menu(&mut self) -> Result<()> {
+        match self.tray.as_mut() {
+            Some(tray) => {
+                tray.set_sound_info(self.audio_player.sound_infos())?;
+                tray.set_schedules(&self.config.schedules)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 終了処理
+    ///
+    /// スケジューラーを止めて実行中の再生を猶予時間まで待ち、残りのイベントを履歴に記録して状態を保存する
+    pub async fn shutdown(&mut self, schedule_events: Option<&mut mpsc::UnboundedReceiver<ScheduleEvent>>) {
+        if let Some(system_tray) = &self.tray {
+            system_tray.shutdown();
+        }
+
+        let grace = std::time::Duration::from_secs(self.config.behavior.shutdown_grace_seconds);
+        self.scheduler.shutdown(grace).await;
+
+        // 終了待ちの間に完了した再生の結果を記録
+        if let Some(events) = schedule_events {
+            while let Ok(event) = events.try_recv() {
+                self.record_event(&event);
+            }
+        }
+
+        self.save_state();
+        if let Some(history) = self.history.as_mut()
+            && let Err(e) = history.flush()
+        {
+            warn!("Failed to flush history file: {:#}", e);
+        }
+
+        // offline の送信を待つ
+        if let Some(mqtt) = self.mqtt.take() {
+            mqtt.wait_closed().await;
+        }
+    }
+
+    /// IPC・`ctl` サブコマンドからの操作を処理
+    ///
+    /// `Quit` はここでは終了せず、呼び出し側で終了処理を行う
+    pub fn handle_control(&mut self, command: ControlCommand) -> ControlResponse {
+        let response = match command {
+            ControlCommand::Status => {
+                let mut response = ControlResponse::ok("Running");
+                response.data = Some(serde_json::json!({
+                    "headless": self.tray.is_none(),
+                    "config_file": self.config_path,
+                    "next_fire": self.scheduler.next_fire(),
+                    "paused_until": self.scheduler.paused_until(),
+                    "muted": self.audio_player.is_muted(),
+                    "volume": self.audio_player.global_volume(),
+                    "errors": self.tray_status.errors,
+                }));
+                response
+            }
+            ControlCommand::Reload => match self.reload_config() {
+                Ok(()) => ControlResponse::ok(format!("Reloaded {} schedule(s)", self.config.schedules.len())),
+                Err(e) => ControlResponse::error(format!("{:#}", e)),
+            },
+            ControlCommand::Pause { minutes, until } => {
+                let until = match (minutes, until) {
+                    (Some(minutes), _) => Ok(Local::now() + chrono::Duration::minutes(minutes as i64)),
+                    (None, Some(until)) => config::parse_time_of_day(&until).and_then(|time| {
+                        Self::next_occurrence(time)
+                            .ok_or_else(|| anyhow::anyhow!("Failed to resolve pause end time {}", time))
+                    }),
+                    (None, None) => Err(anyhow::anyhow!("Specify --minutes or --until")),
+                };
+                match until {
+                    Ok(until) => {
+                        self.scheduler.pause_until(until);
+                        ControlResponse::ok(format!("Paused until {}", until.format("%Y-%m-%d %H:%M")))
+                    }
+                    Err(e) => ControlResponse::error(format!("{:#}", e)),
+                }
+            }
+            ControlCommand::Resume => {
+                self.scheduler.resume();
+                ControlResponse::ok("Resumed")
+            }
+            ControlCommand::Mute => {
+                self.audio_player.set_muted(true);
+                ControlResponse::ok("Muted")
+            }
+            ControlCommand::Unmute => {
+                self.audio_player.set_muted(false);
+                ControlResponse::ok("Unmuted")
+            }
+            ControlCommand::Play { id } => match self.scheduler.trigger(&id) {
+                Ok(()) => ControlResponse::ok(format!("Triggered schedule '{}'", id)),
+                Err(e) => ControlResponse::error(format!("{:#}", e)),
+            },
+            ControlCommand::PlayFile { file } => {
+                let path = self.config.resolve_sound_path(&file);
+                if !path.exists() {
+                    ControlResponse::error(format!("Audio file not found: {} ({})", file, path.display()))
+                } else {
+                    info!("Playing sound file on request: {} -> {:?}", file, path);
+                    let message = format!("Playing {}", path.display());
+                    self.play_file(path);
+                    ControlResponse::ok(message)
+                }
+            }
+            ControlCommand::Stop => {
+                let stopped = self.audio_player.stop_all();
+                ControlResponse::ok(format!("Stopped {} playback(s)", stopped))
+            }
+            ControlCommand::Volume { level } => {
+                self.audio_player.set_global_volume(level);
+                ControlResponse::ok(format!("Volume set to {}", self.audio_player.global_volume()))
+            }
+            ControlCommand::Enable { id } => self.control_set_enabled(&id, true),
+            ControlCommand::Disable { id } => self.control_set_enabled(&id, false),
+            ControlCommand::Quit => ControlResponse::ok("Shutting down"),
+        };
+        self.refresh_tray_status();
+        response
+    }
+
+    fn control_set_enabled(&mut self, schedule_id: &str, enabled: bool) -> ControlResponse {
+        match self.set_schedule_enabled(schedule_id, enabled) {
+            Ok(()) => ControlResponse::ok(format!("Schedule '{}' {}", schedule_id, if enabled { "enabled" } else { "disabled" })),
+            Err(e) => ControlResponse::error(format!("{:#}", e)),
+        }
+    }
+
+    /// トレイメニューイベントを処理（終了要求の場合は true を返す）
+    pub async fn handle_tray_event(&mut self, event: TrayMenuEvent) -> Result<bool> {
+        match event {
+            TrayMenuEvent::ToggleAutoStart => {
+                let Some(system_tray) = self.tray.as_mut() else {
+                    return Ok(false);
+                };
+                let current_status = system_tray.get_autostart_status();
+                let new_status = !current_status;
+
+                match system_tray.set_autostart_status(new_status) {
+                    Ok(()) => {
+                        info!("Auto-start {} {}",
+                              if new_status { "enabled" } else { "disabled" },
+                              if new_status { "✓" } else { "✗" });
+                    }
+                    Err(e) => {
+                        error!("Failed to toggle auto-start: {}", e);
+                    }
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::ToggleSchedule(schedule_id) => {
+                if let Err(e) = self.toggle_schedule(&schedule_id) {
+                    error!("Failed to toggle schedule '{}': {:#}", schedule_id, e);
+                    // チェック状態を実際の設定に戻す
+                    let _ = self.update_tray_menu();
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::PlayScheduleNow(schedule_id) => {
+                if let Err(e) = self.scheduler.trigger(&schedule_id) {
+                    error!("Failed to play schedule '{}': {}", schedule_id, e);
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::PauseFor(minutes) => {
+                self.scheduler.pause_until(Local::now() + chrono::Duration::minutes(minutes as i64));
+                self.refresh_tray_status();
+                Ok(false)
+            }
+
+            TrayMenuEvent::PauseUntil(time) => {
+                match Self::next_occurrence(time) {
+                    Some(until) => self.scheduler.pause_until(until),
+                    None => error!("Failed to resolve pause end time {}", time),
+                }
+                self.refresh_tray_status();
+                Ok(false)
+            }
+
+            TrayMenuEvent::Resume => {
+                self.scheduler.resume();
+                self.refresh_tray_status();
+                Ok(false)
+            }
+
+            TrayMenuEvent::ToggleMute => {
+                self.audio_player.set_muted(!self.audio_player.is_muted());
+                self.refresh_tray_status();
+                Ok(false)
+            }
+
+            TrayMenuEvent::StopPlayback => {
+                self.audio_player.stop_all();
+                Ok(false)
+            }
+
+            TrayMenuEvent::SetVolume(volume) => {
+                let unchanged = self.audio_player.global_volume() == volume;
+                self.audio_player.set_global_volume(volume);
+                self.refresh_tray_status();
+                // 選択中の項目をクリックするとチェックが外れるため、音量が変わらなくてもメニューを作り直す
+                if unchanged
+                    && let Some(tray) = self.tray.as_mut()
+                    && let Err(e) = tray.update_menu()
+                {
+                    warn!("Failed to update tray menu: {}", e);
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::TestVolume => {
+                self.play_test_sound();
+                Ok(false)
+            }
+
+            TrayMenuEvent::SaveVolume => {
+                let volume = self.audio_player.global_volume();
+                self.config.audio.global_volume = volume;
+                match self.config.save_to_file(&self.config_path) {
+                    Ok(()) => info!("Saved global volume {} to config file", volume),
+                    Err(e) => error!("Failed to save global volume to config file: {:#}", e),
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::SnoozeSchedule(schedule_id, minutes) => {
+                if let Err(e) = self.scheduler.snooze(&schedule_id, minutes) {
+                    error!("Failed to snooze schedule '{}': {}", schedule_id, e);
+                }
+                self.sync_pending_fires();
+                Ok(false)
+            }
+
+            TrayMenuEvent::SkipNextRun(schedule_id) => {
+                if let Err(e) = self.scheduler.skip_next(&schedule_id) {
+                    error!("Failed to skip schedule '{}': {}", schedule_id, e);
+                }
+                self.sync_pending_fires();
+                Ok(false)
+            }
+
+            TrayMenuEvent::CancelSkip(schedule_id) => {
+                self.scheduler.cancel_skip(&schedule_id);
+                self.sync_pending_fires();
+                Ok(false)
+            }
+
+            TrayMenuEvent::OpenConfig => {
+                let Some(system_tray) = self.tray.as_ref() else {
+                    return Ok(false);
+                };
+                match system_tray.open_config_file(self.config.ui.editor.as_deref()) {
+                    Ok(Some(mut child)) => {
+                        info!("Opened config file, waiting for editor to close");
+                        // エディタ終了後に設定を再読み込み
+                        let reload_tx = self.reload_tx.clone();
+                        tokio::task::spawn_blocking(move || {
+                            match child.wait() {
+                                Ok(status) => info!("Editor exited with {}", status),
+                                Err(e) => warn!("Failed to wait for editor process: {}", e),
+                            }
+                            let _ = reload_tx.send(());
+                        });
+                    }
+                    Ok(None) => info!("Opened config file"),
+                    Err(e) => error!("Failed to open config file: {}", e),
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::ReloadConfig => {
+                if let Err(e) = self.reload_config() {
+                    error!("{:#}", e);
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::OpenLogsDir => {
+                match SystemTray::open_logs_directory(&self.log_dir) {
+                    Ok(()) => info!("Opened logs directory"),
+                    Err(e) => error!("Failed to open logs directory: {}", e),
+                }
+                Ok(false)
+            }
+
+            TrayMenuEvent::Exit => Ok(true),
+        }
+    }
+}
+
+impl EventHandler for App {
+    async fn on_tray_event(&mut self, event: TrayMenuEvent) -> Result<bool> {
+        self.handle_tray_event(event).await
+    }
+
+    fn on_schedule_event(&mut self, event: ScheduleEvent) {
+        self.record_event(&event);
+        if let Err(e) = self.update_tray_menu() {
+            warn!("Failed to update tray menu: {}", e);
+        }
+        self.sync_pending_fires();
+        self.refresh_tray_status();
+    }
+
+    fn on_control(&mut self, command: ControlCommand) -> ControlResponse {
+        self.handle_control(command)
+    }
+
+    fn on_sound_changes(&mut self, files: Vec<PathBuf>) {
+        self.handle_sound_changes(files);
+    }
+
+    fn on_reload(&mut self) -> Result<()> {
+        self.reload_config()
+    }
+
+    fn refresh_status(&mut self) {
+        self.refresh_tray_status();
+    }
+
+    fn next_status_refresh(&self) -> Option<std::time::Duration> {
+        self.tray.as_ref().map(|_| self.tray_status.next_refresh_in(&Local::now()))
+    }
+}
@@ -1,109 +1,875 @@
 use anyhow::{Context, Result};
-use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use chrono::Local;
+use rodio::mixer::Mixer;
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink, Source};
 use std::collections::HashMap;
-use std::path::Path;
+use std::fmt;
+use std::fs::File;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use crate::config::AudioConfig;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use crate::config::{AudioConfig, FallbackSound, Schedule};
+use crate::scheduler::CronScheduler;
 
 pub struct AudioPlayer {
-    _stream: Arc<OutputStream>,
-    preloaded_sounds: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    /// 出力デバイスのストリーム（破棄すると再生が止まるため保持する）
+    _stream: Option<Arc<OutputStream>>,
+    /// 再生する音声を追加するミキサー
+    mixer: Mixer,
+    preloaded_sounds: Arc<Mutex<HashMap<String, Arc<[u8]>>>>,
+    /// 事前ロード時に取得した音声ファイルの情報
+    sound_info: Mutex<HashMap<String, SoundInfo>>,
     global_volume: Arc<Mutex<f32>>,
+    /// これより大きいファイルはメモリに読み込まずファイルから直接デコードする（バイト）
+    stream_threshold: AtomicU64,
+    /// ミュート中は再生せずに成功扱いにする
+    muted: AtomicBool,
+    /// 再生中の音声（`stop_all` で停止する）
+    active: Arc<Mutex<HashMap<u64, Arc<ActivePlayback>>>>,
+    next_playback_id: AtomicU64,
+    /// 終了時に再生中の音声を停止する
+    shutdown: CancellationToken,
+}
+
+/// 再生時のオプション
+#[derive(Debug, Clone)]
+pub struct PlaybackOptions {
+    /// 再生を開始する位置
+    pub start_at: Option<Duration>,
+    /// 再生を終了する位置
+    pub end_at: Option<Duration>,
+    /// 再生速度（1.0 で等速）
+    pub speed: f32,
+    /// 開始〜終了位置の区間を再生する回数
+    pub loop_count: u32,
+    /// 再生する最大時間（繰り返しを含む。超えた分は再生しない）
+    pub max_duration: Option<Duration>,
+}
+
+impl Default for PlaybackOptions {
+    fn default() -> Self {
+        Self {
+            start_at: None,
+            end_at: None,
+            speed: 1.0,
+            loop_count: 1,
+            max_duration: None,
+        }
+    }
+}
+
+impl PlaybackOptions {
+    pub fn for_schedule(schedule: &Schedule) -> Self {
+        Self {
+            start_at: schedule.start_at.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()),
+            end_at: schedule.end_at.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()),
+            speed: schedule.speed.filter(|speed| speed.is_finite() && *speed > 0.0).unwrap_or(1.0),
+            loop_count: schedule.loop_count.unwrap_or(1).max(1),
+            max_duration: schedule.max_duration_seconds.map(Duration::from_secs),
+        }
+    }
+
+    /// 音声の長さ `total` に対して、区間・速度・繰り返し・最大時間を反映した再生時間
+    pub fn playback_length(&self, total: Duration) -> Duration {
+        let start_at = self.start_at.unwrap_or_default().min(total);
+        let end_at = self.end_at.unwrap_or(total).min(total);
+        let length = end_at.saturating_sub(start_at).div_f32(self.speed) * self.loop_count;
+        match self.max_duration {
+            Some(max_duration) => length.min(max_duration),
+            None => length,
+        }
+    }
+
+    /// 音声の長さに対して開始・終了位置が有効か確認
+    fn check_duration(&self, total: Duration) -> Result<()> {
+        for (name, position) in [("start_at", self.start_at), ("end_at", self.end_at)] {
+            if let Some(position) = position
+                && position > total
+            {
+                return Err(anyhow::anyhow!(
+                    "{} ({:.1}s) is beyond the end of the audio ({:.1}s)",
+                    name,
+                    position.as_secs_f64(),
+                    total.as_secs_f64()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 開始・終了位置と速度を適用
+    fn apply_segment(&self, source: Box<dyn Source + Send>) -> Box<dyn Source + Send> {
+        let start_at = self.start_at.unwrap_or_default();
+        let mut source = source;
+        if !start_at.is_zero() {
+            source = Box::new(source.skip_duration(start_at));
+        }
+        if let Some(end_at) = self.end_at {
+            source = Box::new(source.take_duration(end_at.saturating_sub(start_at)));
+        }
+        if self.speed != 1.0 {
+            source = Box::new(source.speed(self.speed));
+        }
+        source
+    }
+}
+
+/// デコードして取得した音声ファイルの情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundInfo {
+    /// 長さ（不明な場合は None）
+    pub duration: Option<Duration>,
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// 形式（拡張子）
+    pub format: String,
+    /// ピークレベル（0.0-1.0。ストリーミング再生するファイルでは計測しない）
+    pub peak: Option<f32>,
+}
+
+impl SoundInfo {
+    /// デコーダーから情報を取得（`scan` が true の場合は全体をデコードしてピークを計測する）
+    fn from_source<S: Source>(mut source: S, format: String, scan: bool) -> Self {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let mut duration = source.total_duration();
+        let mut peak = None;
+
+        if scan {
+            let mut samples = 0u64;
+            let mut max = 0.0f32;
+            for sample in source.by_ref() {
+                samples += 1;
+                max = max.max(sample.abs());
+            }
+            peak = Some(max.min(1.0));
+            // 長さが分からない形式はサンプル数から求める
+            if duration.is_none() && channels > 0 && sample_rate > 0 {
+                duration = Some(Duration::from_secs_f64(samples as f64 / (channels as f64 * sample_rate as f64)));
+            }
+        }
+
+        Self {
+            duration,
+            channels,
+            sample_rate,
+            format,
+            peak,
+        }
+    }
+
+    /// ピークレベル（dBFS）
+    pub fn peak_dbfs(&self) -> Option<f32> {
+        self.peak.map(|peak| 20.0 * peak.max(f32::MIN_POSITIVE).log10())
+    }
+}
+
+impl fmt::Display for SoundInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.duration {
+            Some(duration) => write!(f, "{:.1}s", duration.as_secs_f64())?,
+            None => write!(f, "?s")?,
+        }
+        write!(f, ", {}ch, {}Hz, {}", self.channels, self.sample_rate, self.format)?;
+        if let Some(dbfs) = self.peak_dbfs() {
+            write!(f, ", peak {:.1} dBFS", dbfs)?;
+        }
+        Ok(())
+    }
+}
+
+/// 再生中の音声
+struct ActivePlayback {
+    sink: Arc<Sink>,
+    /// `stop_all` で停止された
+    stopped: AtomicBool,
+}
+
+/// 組み込みの音の名前（代替音声に `builtin:<名前>` で指定できる）
+pub const BUILTIN_SOUNDS: [&str; 1] = ["beep"];
+
+/// 組み込みのビープ音
+const BEEP_FREQUENCY: f32 = 880.0;
+const BEEP_DURATION: Duration = Duration::from_millis(500);
+const BEEP_AMPLITUDE: f32 = 0.5;
+
+/// 再生する音声データの読み込み元
+enum AudioInput {
+    /// メモリ上のデータ（事前ロード済みまたは小さいファイル）
+    Memory(Arc<[u8]>),
+    /// ファイルから逐次デコード
+    Stream(PathBuf),
+    /// 組み込みの音（`BUILTIN_SOUNDS` のいずれか）
+    Builtin(&'static str),
+}
+
+impl AudioInput {
+    /// デコーダーを作成（繰り返し再生では回数分作成する）
+    fn decode(&self) -> Result<Box<dyn Source + Send>> {
+        match self {
+            AudioInput::Memory(data) => {
+                let decoder = Decoder::new(Cursor::new(data.clone())).context("Failed to decode audio")?;
+                Ok(Box::new(decoder))
+            }
+            AudioInput::Stream(path) => {
+                let file = File::open(path).context("Failed to open audio file")?;
+                let decoder = Decoder::try_from(file).context("Failed to decode audio")?;
+                Ok(Box::new(decoder))
+            }
+            AudioInput::Builtin("beep") => {
+                let beep = rodio::source::SineWave::new(BEEP_FREQUENCY)
+                    .take_duration(BEEP_DURATION)
+                    .amplify(BEEP_AMPLITUDE);
+                Ok(Box::new(beep))
+            }
+            AudioInput::Builtin(name) => Err(anyhow::anyhow!("Unknown builtin sound: {}", name)),
+        }
+    }
 }
 
 impl AudioPlayer {
     pub fn new(config: &AudioConfig) -> Result<Self> {
-        let global_volume = (config.global_volume as f32) / 100.0;
-
         // OutputStreamBuilder を使用
         let stream = OutputStreamBuilder::open_default_stream()
             .map_err(|e| anyhow::anyhow!("Failed to open default audio stream: {}", e))?;
+        let mixer = stream.mixer().clone();
+
+        Ok(Self::with_output(Some(Arc::new(stream)), mixer, config))
+    }
 
-        Ok(Self {
-            _stream: Arc::new(stream),
+    fn with_output(stream: Option<Arc<OutputStream>>, mixer: Mixer, config: &AudioConfig) -> Self {
+        let global_volume = (config.global_volume as f32) / 100.0;
+
+        Self {
+            _stream: stream,
+            mixer,
             preloaded_sounds: Arc::new(Mutex::new(HashMap::new())),
+            sound_info: Mutex::new(HashMap::new()),
             global_volume: Arc::new(Mutex::new(global_volume)),
-        })
+            stream_threshold: AtomicU64::new(config.stream_threshold_bytes()),
+            muted: AtomicBool::new(false),
+            active: Arc::new(Mutex::new(HashMap::new())),
+            next_playback_id: AtomicU64::new(0),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// 出力デバイスを使わずに再生する（ミキサーの出力を別スレッドで読み捨てるため、実際の長さより速く終わる）
+    #[cfg(test)]
+    pub fn silent(config: &AudioConfig) -> Self {
+        let (mixer, mut source) = rodio::mixer::mixer(2, 44_100);
+        std::thread::spawn(move || loop {
+            if source.by_ref().take(8_820).count() == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        Self::with_output(None, mixer, config)
+    }
+
+    /// 全体の音量を設定（0-100、次回の再生から反映）
+    pub fn set_global_volume(&self, volume: u8) {
+        let volume = volume.min(100);
+        *self.global_volume.lock().unwrap() = volume as f32 / 100.0;
+        tracing::info!("Global volume set to {}", volume);
+    }
+
+    /// 現在の全体の音量（0-100）
+    pub fn global_volume(&self) -> u8 {
+        (*self.global_volume.lock().unwrap() * 100.0).round() as u8
+    }
+
+    /// ストリーミング再生に切り替えるファイルサイズを設定（次回の事前ロード・再生から反映）
+    pub fn set_stream_threshold(&self, config: &AudioConfig) {
+        self.stream_threshold.store(config.stream_threshold_bytes(), Ordering::Relaxed);
+    }
+
+    /// ミュートを設定
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+        tracing::info!("Audio {}", if muted { "muted" } else { "unmuted" });
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    /// 再生中の音声をすべて停止し、停止した数を返す
+    ///
+    /// 停止された再生は成功として扱う
+    pub fn stop_all(&self) -> usize {
+        let active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        for playback in active.values() {
+            playback.stopped.store(true, Ordering::SeqCst);
+            playback.sink.stop();
+        }
+        if !active.is_empty() {
+            tracing::info!("Stopped {} audio playback(s)", active.len());
+        }
+        active.len()
+    }
+
+    /// 再生中の音声を停止し、以降の再生要求をエラーにする（終了処理用）
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+        self.stop_all();
+        tracing::info!("Audio playback stopped for shutdown");
+    }
+
+    /// 終了処理中か
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
     }
 
     /// 音声ファイルを事前にメモリにロード
-    pub fn preload_sound<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+    ///
+    /// ストリーミング再生の対象となる大きなファイルはデコードできるかの確認のみ行う
+    /// 長さが分かる音声では `options` の開始・終了位置が範囲内かも確認する
+    pub fn preload_sound<P: AsRef<Path>>(&self, file_path: P, options: &PlaybackOptions) -> Result<()> {
         let path = file_path.as_ref();
         let path_str = path.to_string_lossy().to_string();
 
-        if !path.exists() {
-            return Err(anyhow::anyhow!("Audio file not found: {:?}", path));
+        let (audio_data, info) = load_sound(path, options, self.stream_threshold.load(Ordering::Relaxed))?;
+        match audio_data {
+            Some(data) => {
+                self.preloaded_sounds.lock().unwrap().insert(path_str.clone(), data);
+                tracing::info!(file = %path.display(), info = %info, "Preloaded audio file");
+            }
+            None => {
+                self.preloaded_sounds.lock().unwrap().remove(&path_str);
+                tracing::info!(file = %path.display(), info = %info, "Audio file will be streamed from disk");
+            }
         }
+        self.sound_info.lock().unwrap().insert(path_str, info);
+        Ok(())
+    }
 
-        let audio_data = std::fs::read(path)
-            .with_context(|| format!("Failed to read audio file: {:?}", path))?;
+    /// 事前ロードしたデータと情報を破棄（ファイルが削除された場合など）
+    pub fn forget_sound<P: AsRef<Path>>(&self, file_path: P) {
+        let path_str = file_path.as_ref().to_string_lossy().to_string();
+        self.preloaded_sounds.lock().unwrap().remove(&path_str);
+        self.sound_info.lock().unwrap().remove(&path_str);
+    }
 
-        // デコードテストを実行して有効な音声ファイルかチェック
-        let cursor = std::io::Cursor::new(audio_data.clone());
-        let _decoder = Decoder::new(cursor)
-            .with_context(|| format!("Failed to decode audio file: {:?}", path))?;
+    /// 事前ロード時に取得した音声ファイルの情報
+    pub fn sound_info<P: AsRef<Path>>(&self, file_path: P) -> Option<SoundInfo> {
+        let path_str = file_path.as_ref().to_string_lossy().to_string();
+        self.sound_info.lock().unwrap().get(&path_str).cloned()
+    }
 
-        let mut preloaded = self.preloaded_sounds.lock().unwrap();
-        preloaded.insert(path_str.clone(), audio_data);
+    /// 事前ロード済みのすべての音声ファイルの情報（ファイルパスごと）
+    pub fn sound_infos(&self) -> HashMap<String, SoundInfo> {
+        self.sound_info.lock().unwrap().clone()
+    }
 
-        tracing::info!("Preloaded audio file: {:?}", path);
-        Ok(())
+    /// ファイルサイズがしきい値を超えているか
+    fn should_stream(&self, path: &Path) -> Result<bool> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Failed to read audio file metadata: {:?}", path))?
+            .len();
+        Ok(size > self.stream_threshold.load(Ordering::Relaxed))
     }
 
     /// 音声を非同期で再生（ブロッキングしない）
-    pub async fn play_sound<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+    pub async fn play_sound<P: AsRef<Path>>(&self, file_path: P, options: &PlaybackOptions) -> Result<()> {
         let path = file_path.as_ref();
         let path_str = path.to_string_lossy().to_string();
 
         tracing::debug!("Attempting to play sound: {:?}", path);
 
+        if !self.can_play(&path_str)? {
+            return Ok(());
+        }
+
         // 事前ロードされた音声データを取得
         let audio_data = {
             let preloaded = self.preloaded_sounds.lock().unwrap();
             preloaded.get(&path_str).cloned()
         };
 
-        let audio_data = match audio_data {
-            Some(data) => data,
+        let input = match audio_data {
+            Some(data) => AudioInput::Memory(data),
+            None if self.should_stream(path)? => {
+                tracing::debug!("Streaming audio file from disk: {:?}", path);
+                AudioInput::Stream(path.to_path_buf())
+            }
             None => {
                 // 事前ロードされていない場合はファイルから読み込み
                 tracing::warn!("Audio file not preloaded, loading from disk: {:?}", path);
-                std::fs::read(path)
-                    .with_context(|| format!("Failed to read audio file: {:?}", path))?
+                let data = std::fs::read(path)
+                    .with_context(|| format!("Failed to read audio file: {:?}", path))?;
+                AudioInput::Memory(data.into())
+            }
+        };
+
+        self.play_input(input, path_str, options).await
+    }
+
+    /// 組み込みの音を再生
+    pub async fn play_builtin(&self, name: &str, options: &PlaybackOptions) -> Result<()> {
+        let builtin = BUILTIN_SOUNDS
+            .into_iter()
+            .find(|builtin| *builtin == name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown builtin sound: {}", name))?;
+        let label = format!("builtin:{}", builtin);
+        if !self.can_play(&label)? {
+            return Ok(());
+        }
+        self.play_input(AudioInput::Builtin(builtin), label, options).await
+    }
+
+    /// 音声を再生し、失敗した場合は代替音声を順に試す
+    ///
+    /// 成功した場合は使用した代替音声を返す（元の音声を再生できた場合は None）。代替音声は再生区間・速度・繰り返しを適用せずに再生する
+    pub async fn play_with_fallback(&self, file_path: &Path, fallbacks: &[FallbackSound], options: &PlaybackOptions) -> Result<Option<FallbackSound>> {
+        let mut errors = Vec::new();
+        match self.play_sound(file_path, options).await {
+            Ok(()) => return Ok(None),
+            Err(e) if fallbacks.is_empty() || self.is_shutting_down() => return Err(e),
+            Err(e) => {
+                tracing::warn!(file = %file_path.display(), error = %format!("{:#}", e), "Failed to play sound, trying fallback sounds");
+                errors.push(format!("{:#}", e));
             }
+        }
+
+        let fallback_options = PlaybackOptions {
+            max_duration: options.max_duration,
+            ..PlaybackOptions::default()
         };
+        for fallback in fallbacks {
+            let result = match fallback {
+                FallbackSound::File(path) => self.play_sound(path, &fallback_options).await,
+                FallbackSound::Builtin(name) => self.play_builtin(name, &fallback_options).await,
+            };
+            match result {
+                Ok(()) => {
+                    tracing::info!(fallback = %fallback, outcome = "succeeded", "Played fallback sound");
+                    return Ok(Some(fallback.clone()));
+                }
+                Err(e) => {
+                    tracing::warn!(fallback = %fallback, outcome = "failed", error = %format!("{:#}", e), "Failed to play fallback sound");
+                    errors.push(format!("{}: {:#}", fallback, e));
+                    if self.is_shutting_down() {
+                        break;
+                    }
+                }
+            }
+        }
+        Err(anyhow::anyhow!("{}", errors.join("; ")))
+    }
 
+    /// 再生できる状態か確認（ミュート中は false、終了処理中はエラー）
+    fn can_play(&self, label: &str) -> Result<bool> {
+        if self.is_muted() {
+            tracing::info!(file = label, outcome = "muted", "Audio is muted, skipping playback");
+            return Ok(false);
+        }
+        if self.shutdown.is_cancelled() {
+            return Err(anyhow::anyhow!("Audio player is shutting down"));
+        }
+        Ok(true)
+    }
+
+    /// 入力を再生し、完了・停止まで待つ
+    async fn play_input(&self, input: AudioInput, path_str: String, options: &PlaybackOptions) -> Result<()> {
         // 非同期タスクで再生実行
         let global_volume = *self.global_volume.lock().unwrap();
         let path_for_log = path_str.clone();
-        let stream_ref = self._stream.clone();
-
-        tokio::task::spawn_blocking(move || -> Result<()> {
-            tracing::debug!("Starting audio playback task for: {}", path_for_log);
-            
-            // 既存のストリームを使用
-            let sink = Sink::connect_new(&stream_ref.mixer());
-            
-            // デコーダーを作成
-            let cursor = std::io::Cursor::new(audio_data);
-            let decoder = Decoder::new(cursor)
-                .with_context(|| format!("Failed to decode audio: {}", path_for_log))?;
-
-            tracing::debug!("Setting volume to {} for: {}", global_volume, path_for_log);
+        // 既存のストリームを使用（停止・終了時に止められるよう登録しておく）
+        let playback_id = self.next_playback_id.fetch_add(1, Ordering::Relaxed);
+        let playback = Arc::new(ActivePlayback {
+            sink: Arc::new(Sink::connect_new(&self.mixer)),
+            stopped: AtomicBool::new(false),
+        });
+        self.active.lock().unwrap_or_else(|e| e.into_inner()).insert(playback_id, playback.clone());
+        let task_playback = playback.clone();
+        let shutdown = self.shutdown.clone();
+        let options = options.clone();
+
+        let result = tokio::task::spawn_blocking(move || -> Result<()> {
+            tracing::debug!(file = %path_for_log, "Starting audio playback task");
+            let sink = &task_playback.sink;
+
+            tracing::debug!(file = %path_for_log, volume = global_volume, "Setting playback volume");
             sink.set_volume(global_volume);
-            
-            tracing::debug!("Starting audio stream for: {}", path_for_log);
-            sink.append(decoder);
+
+            // デコーダーを作成して再生を開始
+            tracing::debug!(file = %path_for_log, "Starting audio stream");
+            let source = build_source(input, &options)
+                .with_context(|| format!("Failed to prepare audio: {}", path_for_log))?;
+            sink.append(source);
+            // append は停止状態を解除するため、先に停止・終了要求が来ていた場合はここで停止する
+            if shutdown.is_cancelled() {
+                task_playback.stopped.store(true, Ordering::SeqCst);
+            }
+            if task_playback.stopped.load(Ordering::SeqCst) {
+                sink.stop();
+            }
 
             // 再生完了まで待機
-            tracing::debug!("Waiting for audio completion: {}", path_for_log);
+            tracing::debug!(file = %path_for_log, "Waiting for audio completion");
             sink.sleep_until_end();
-            
-            tracing::info!("Successfully completed audio playback: {}", path_for_log);
             Ok(())
         })
         .await
         .context("Audio playback task failed")
-        .and_then(|result| result)
+        .and_then(|result| result);
+
+        self.active.lock().unwrap_or_else(|e| e.into_inner()).remove(&playback_id);
+        result?;
+
+        if playback.stopped.load(Ordering::SeqCst) {
+            if self.shutdown.is_cancelled() {
+                return Err(anyhow::anyhow!("Audio playback stopped by shutdown: {}", path_str));
+            }
+            tracing::info!(file = %path_str, outcome = "stopped", "Audio playback stopped on request");
+        } else {
+            tracing::info!(file = %path_str, outcome = "completed", "Audio playback completed");
+        }
+        Ok(())
+    }
+}
+
+/// 音声ファイルをデコードして情報を取得し、再生オプションを確認（`validate` 用。データは保持しない）
+pub fn inspect_sound<P: AsRef<Path>>(file_path: P, options: &PlaybackOptions, stream_threshold: u64) -> Result<SoundInfo> {
+    load_sound(file_path.as_ref(), options, stream_threshold).map(|(_, info)| info)
+}
+
+/// 音声ファイルを読み込んでデコードし、情報を取得して再生オプションを確認
+///
+/// `stream_threshold` を超えるファイルはストリーミング再生の対象としてデータを返さず、ピークも計測しない
+fn load_sound(path: &Path, options: &PlaybackOptions, stream_threshold: u64) -> Result<(Option<Arc<[u8]>>, SoundInfo)> {
+    if !path.exists() {
+        return Err(anyhow::anyhow!("Audio file not found: {:?}", path));
+    }
+
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read audio file metadata: {:?}", path))?
+        .len();
+    let format = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_uppercase())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let (audio_data, info) = if size > stream_threshold {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open audio file: {:?}", path))?;
+        let decoder = Decoder::try_from(file)
+            .with_context(|| format!("Failed to decode audio file: {:?}", path))?;
+        (None, SoundInfo::from_source(decoder, format, false))
+    } else {
+        let audio_data: Arc<[u8]> = std::fs::read(path)
+            .with_context(|| format!("Failed to read audio file: {:?}", path))?
+            .into();
+        // 全体をデコードして有効な音声ファイルかチェック
+        let decoder = Decoder::new(Cursor::new(audio_data.clone()))
+            .with_context(|| format!("Failed to decode audio file: {:?}", path))?;
+        (Some(audio_data), SoundInfo::from_source(decoder, format, true))
+    };
+
+    match info.duration {
+        Some(total) => options
+            .check_duration(total)
+            .with_context(|| format!("Invalid playback range for audio file: {:?}", path))?,
+        None => tracing::debug!("Audio duration is unknown, skipping playback range check: {:?}", path),
+    }
+    Ok((audio_data, info))
+}
+
+/// 再生時間がスケジュールの実行間隔より長い場合の警告メッセージ
+pub fn check_fire_interval(schedule: &Schedule, info: &SoundInfo) -> Option<String> {
+    let length = PlaybackOptions::for_schedule(schedule).playback_length(info.duration?);
+    let interval = CronScheduler::min_fire_interval(schedule, &Local::now())?.to_std().ok()?;
+    (length > interval).then(|| {
+        format!(
+            "audio plays for {:.1}s but the schedule fires every {}s",
+            length.as_secs_f64(),
+            interval.as_secs()
+        )
+    })
+}
+
+/// オプションを適用した再生用のソースを作成
+///
+/// 繰り返しは区間ごとにデコーダーを作り直して連結する（ストリーミング再生でも全体をメモリに持たない）
+fn build_source(input: AudioInput, options: &PlaybackOptions) -> Result<Box<dyn Source + Send>> {
+    let first = input.decode()?;
+    let repeats = (1..options.loop_count).map_while(move |_| match input.decode() {
+        Ok(source) => Some(source),
+        Err(e) => {
+            tracing::warn!("Failed to decode audio for repeat, stopping loop: {:#}", e);
+            None
+        }
+    });
+    let segment_options = options.clone();
+    let segments = std::iter::once(first)
+        .chain(repeats)
+        .map(move |source| segment_options.apply_segment(source));
+
+    let source: Box<dyn Source + Send> = Box::new(rodio::source::from_iter(segments));
+    Ok(match options.max_duration {
+        Some(max_duration) => Box::new(source.take_duration(max_duration)),
+        None => source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audio_config() -> AudioConfig {
+        AudioConfig {
+            global_volume: 50,
+            stream_threshold_mb: 8,
+            sounds_dir: None,
+            fallback_sound: None,
+        }
+    }
+
+    fn secs(seconds: f64) -> Duration {
+        Duration::from_secs_f64(seconds)
+    }
+
+    fn options(start_at: Option<f64>, end_at: Option<f64>, speed: f32, loop_count: u32, max_duration: Option<f64>) -> PlaybackOptions {
+        PlaybackOptions {
+            start_at: start_at.map(secs),
+            end_at: end_at.map(secs),
+            speed,
+            loop_count,
+            max_duration: max_duration.map(secs),
+        }
+    }
+
+    #[test]
+    fn playback_length_applies_segment_speed_loop_and_max_duration() {
+        // (開始位置, 終了位置, 速度, 回数, 最大時間, 期待する再生時間) 音声の長さは 10 秒
+        let cases = [
+            (None, None, 1.0, 1, None, 10.0),
+            (Some(2.0), None, 1.0, 1, None, 8.0),
+            (None, Some(4.0), 1.0, 1, None, 4.0),
+            (Some(2.0), Some(6.0), 1.0, 1, None, 4.0),
+            (Some(2.0), Some(6.0), 2.0, 1, None, 2.0),
+            (Some(2.0), Some(6.0), 0.5, 1, None, 8.0),
+            (Some(2.0), Some(6.0), 1.0, 3, None, 12.0),
+            (Some(2.0), Some(6.0), 2.0, 3, None, 6.0),
+            (None, None, 1.0, 3, Some(25.0), 25.0),
+            (None, None, 1.0, 1, Some(25.0), 10.0),
+            // 範囲外の位置は音声の長さで切り詰める
+            (Some(12.0), None, 1.0, 1, None, 0.0),
+            (Some(6.0), Some(15.0), 1.0, 1, None, 4.0),
+            // 終了位置が開始位置より前なら再生しない
+            (Some(6.0), Some(2.0), 1.0, 2, None, 0.0),
+        ];
+        for (start_at, end_at, speed, loop_count, max_duration, expected) in cases {
+            let options = options(start_at, end_at, speed, loop_count, max_duration);
+            let length = options.playback_length(secs(10.0));
+            assert!((length.as_secs_f64() - expected).abs() < 1e-6, "{:?}: {:?}", options, length);
+        }
+    }
+
+    #[test]
+    fn check_duration_rejects_positions_beyond_the_end() {
+        // (開始位置, 終了位置, 期待するエラー) 音声の長さは 10 秒
+        let cases = [
+            (None, None, None),
+            (Some(0.0), Some(10.0), None),
+            (Some(9.5), None, None),
+            (Some(10.5), None, Some("start_at (10.5s) is beyond the end of the audio (10.0s)")),
+            (None, Some(12.25), Some("end_at (12.2s) is beyond the end of the audio (10.0s)")),
+            (Some(11.0), Some(12.0), Some("start_at (11.0s) is beyond the end of the audio (10.0s)")),
+        ];
+        for (start_at, end_at, expected) in cases {
+            let options = options(start_at, end_at, 1.0, 1, None);
+            let result = options.check_duration(secs(10.0)).map_err(|e| e.to_string());
+            assert_eq!(result.err().as_deref(), expected, "{:?}", options);
+        }
+    }
+
+    /// 16bit モノラルの WAV ファイルを書き出す
+    fn write_wav(path: &Path, sample_rate: u32, samples: &[i16]) {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::with_capacity(44 + samples.len() * 2);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        std::fs::write(path, bytes).unwrap();
     }
-}
\ No newline at end of file
+
+    /// 再生できるファイル・存在しないファイル・壊れたファイルを用意する
+    fn sound_files(dir: &Path) -> (PathBuf, PathBuf, PathBuf) {
+        let valid = dir.join("valid.wav");
+        write_wav(&valid, 8_000, &[i16::MAX / 2; 800]);
+        let corrupt = dir.join("corrupt.wav");
+        std::fs::write(&corrupt, b"not a wav file").unwrap();
+        (valid, dir.join("missing.wav"), corrupt)
+    }
+
+    #[tokio::test]
+    async fn play_with_fallback_plays_primary_without_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let (valid, missing, _) = sound_files(dir.path());
+        let player = AudioPlayer::silent(&audio_config());
+
+        let fallbacks = [FallbackSound::File(missing), FallbackSound::Builtin("beep".to_string())];
+        let used = player.play_with_fallback(&valid, &fallbacks, &PlaybackOptions::default()).await.unwrap();
+        assert_eq!(used, None);
+    }
+
+    #[tokio::test]
+    async fn play_with_fallback_tries_fallbacks_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (valid, missing, corrupt) = sound_files(dir.path());
+        let player = AudioPlayer::silent(&audio_config());
+
+        // 存在しない・壊れた代替音声は飛ばし、最初に再生できたものを返す（以降の組み込みの音は使わない）
+        let fallbacks = [
+            FallbackSound::File(missing.clone()),
+            FallbackSound::File(corrupt.clone()),
+            FallbackSound::File(valid),
+            FallbackSound::Builtin("beep".to_string()),
+        ];
+        let used = player.play_with_fallback(&missing, &fallbacks, &PlaybackOptions::default()).await.unwrap();
+        assert_eq!(used, Some(fallbacks[2].clone()));
+
+        // 再生できるファイルがなければ最後の組み込みの音を使う
+        let fallbacks = [FallbackSound::File(missing), FallbackSound::Builtin("beep".to_string())];
+        let used = player.play_with_fallback(&corrupt, &fallbacks, &PlaybackOptions::default()).await.unwrap();
+        assert_eq!(used, Some(FallbackSound::Builtin("beep".to_string())));
+    }
+
+    #[tokio::test]
+    async fn play_with_fallback_reports_every_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, missing, corrupt) = sound_files(dir.path());
+        let player = AudioPlayer::silent(&audio_config());
+
+        let error = player.play_with_fallback(&missing, &[], &PlaybackOptions::default()).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("missing.wav"), "{:#}", error);
+
+        let fallbacks = [FallbackSound::File(corrupt), FallbackSound::Builtin("chime".to_string())];
+        let error = player.play_with_fallback(&missing, &fallbacks, &PlaybackOptions::default()).await.unwrap_err().to_string();
+        let messages: Vec<_> = error.split("; ").collect();
+        assert_eq!(messages.len(), 3, "{}", error);
+        assert!(messages[0].contains("missing.wav"), "{}", error);
+        assert!(messages[1].contains("corrupt.wav") && messages[1].contains("Failed to decode audio"), "{}", error);
+        assert_eq!(messages[2], "builtin:chime: Unknown builtin sound: chime");
+    }
+
+    #[test]
+    fn inspect_sound_streams_files_above_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.wav");
+        write_wav(&path, 8_000, &[i16::MAX / 2; 8_000]);
+        let size = std::fs::metadata(&path).unwrap().len();
+
+        // しきい値以下ならメモリに読み込んで全体をデコードする（ピークを計測する）
+        let info = inspect_sound(&path, &PlaybackOptions::default(), size).unwrap();
+        assert!(info.peak.is_some());
+        // しきい値を超えるとストリーミング再生の対象としてピークを計測しない
+        let info = inspect_sound(&path, &PlaybackOptions::default(), size - 1).unwrap();
+        assert_eq!(info.peak, None);
+        assert_eq!(info.duration, Some(Duration::from_secs(1)));
+
+        // ストリーミング再生の対象でも再生区間は確認する
+        let options = PlaybackOptions {
+            start_at: Some(Duration::from_secs(2)),
+            ..PlaybackOptions::default()
+        };
+        let error = inspect_sound(&path, &options, size - 1).unwrap_err();
+        assert!(format!("{:#}", error).contains("start_at (2.0s) is beyond the end of the audio (1.0s)"), "{:#}", error);
+    }
+
+    #[tokio::test]
+    async fn preload_sound_keeps_only_small_files_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let (valid, _, corrupt) = sound_files(dir.path());
+        let player = AudioPlayer::silent(&audio_config());
+        let is_preloaded = |path: &Path| player.preloaded_sounds.lock().unwrap().contains_key(&*path.to_string_lossy());
+
+        player.preload_sound(&valid, &PlaybackOptions::default()).unwrap();
+        assert!(is_preloaded(&valid));
+
+        // しきい値を 0 にするとすべてのファイルをストリーミング再生する（事前ロード済みのデータも破棄する）
+        player.set_stream_threshold(&AudioConfig { stream_threshold_mb: 0, ..audio_config() });
+        player.preload_sound(&valid, &PlaybackOptions::default()).unwrap();
+        assert!(!is_preloaded(&valid));
+        assert!(player.should_stream(&valid).unwrap());
+        assert!(player.sound_info(&valid).is_some_and(|info| info.peak.is_none()));
+        player.play_sound(&valid, &PlaybackOptions::default()).await.unwrap();
+
+        // ストリーミング再生の対象でもデコードできないファイルはエラーにする
+        assert!(player.preload_sound(&corrupt, &PlaybackOptions::default()).is_err());
+    }
+
+    #[test]
+    fn inspect_sound_reports_decoded_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let (valid, _, _) = sound_files(dir.path());
+
+        let info = inspect_sound(&valid, &PlaybackOptions::default(), u64::MAX).unwrap();
+        assert_eq!(info.duration, Some(Duration::from_millis(100)));
+        assert_eq!((info.channels, info.sample_rate, info.format.as_str()), (1, 8_000, "WAV"));
+        assert_eq!(info.to_string(), "0.1s, 1ch, 8000Hz, WAV, peak -6.0 dBFS");
+
+        let player = AudioPlayer::silent(&audio_config());
+        player.preload_sound(&valid, &PlaybackOptions::default()).unwrap();
+        assert_eq!(player.sound_info(&valid), Some(info));
+        player.forget_sound(&valid);
+        assert_eq!(player.sound_info(&valid), None);
+    }
+
+    #[test]
+    fn sound_info_display_omits_unknown_values() {
+        let mut info = SoundInfo {
+            duration: None,
+            channels: 2,
+            sample_rate: 44_100,
+            format: "MP3".to_string(),
+            peak: None,
+        };
+        assert_eq!(info.to_string(), "?s, 2ch, 44100Hz, MP3");
+
+        info.duration = Some(Duration::from_millis(2_760));
+        info.peak = Some(1.0);
+        assert_eq!(info.to_string(), "2.8s, 2ch, 44100Hz, MP3, peak 0.0 dBFS");
+        // 無音はピークが 0 でも有限の値で表示する
+        info.peak = Some(0.0);
+        assert!(info.peak_dbfs().is_some_and(f32::is_finite));
+    }
+
+    #[test]
+    fn check_fire_interval_warns_when_playback_outlasts_interval() {
+        let schedule: Schedule =
+            serde_yaml::from_str("id: every_two_seconds\ntype: cron\ncron: \"*/2 * * * * *\"\nfile: chime.wav\nenabled: true\n").unwrap();
+        let mut info = SoundInfo {
+            duration: Some(Duration::from_secs(5)),
+            channels: 1,
+            sample_rate: 8_000,
+            format: "WAV".to_string(),
+            peak: None,
+        };
+        assert_eq!(
+            check_fire_interval(&schedule, &info).as_deref(),
+            Some("audio plays for 5.0s but the schedule fires every 2s")
+        );
+
+        info.duration = Some(Duration::from_secs(1));
+        assert_eq!(check_fire_interval(&schedule, &info), None);
+        info.duration = None;
+        assert_eq!(check_fire_interval(&schedule, &info), None);
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::{Context, Result};
+#[cfg(not(target_os = "windows"))]
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// ログイン時の自動起動を管理する
+pub trait AutoStart: Send + Sync {
+    /// 自動起動が有効かどうか
+    fn is_enabled(&self) -> Result<bool>;
+
+    /// 自動起動を有効化/無効化
+    fn set_enabled(&self, enabled: bool) -> Result<()>;
+}
+
+/// 現在のプラットフォーム向けの自動起動実装を作成
+///
+/// `args` は自動起動時に実行ファイルへ渡す引数（`--config` など）
+pub fn platform_autostart(args: Vec<OsString>) -> Box<dyn AutoStart> {
+    let exe_path = match std::env::current_exe() {
+        Ok(path) => path,
+        Err(e) => {
+            tracing::warn!("Failed to get current executable path, autostart is unavailable: {}", e);
+            return Box::new(Unsupported);
+        }
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        Box::new(WindowsStartupShortcut { exe_path, args })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(LaunchAgent { exe_path, args })
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        Box::new(XdgAutostart { exe_path, args })
+    }
+
+    #[cfg(not(any(target_os = "windows", unix)))]
+    {
+        let _ = (exe_path, args);
+        Box::new(Unsupported)
+    }
+}
+
+/// 自動起動に対応していない環境
+pub struct Unsupported;
+
+impl AutoStart for Unsupported {
+    fn is_enabled(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn set_enabled(&self, _enabled: bool) -> Result<()> {
+        tracing::info!("Autostart setting not implemented for this platform");
+        Ok(())
+    }
+}
+
+/// Windows: スタートアップフォルダのショートカット
+#[cfg(target_os = "windows")]
+pub struct WindowsStartupShortcut {
+    exe_path: PathBuf,
+    args: Vec<OsString>,
+}
+
+#[cfg(target_os = "windows")]
+impl WindowsStartupShortcut {
+    fn shortcut_path() -> Result<PathBuf> {
+        use directories::BaseDirs;
+
+        // directories クレートを使用してスタートアップフォルダのパスを取得
+        let base_dirs = BaseDirs::new()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get base directories"))?;
+
+        let startup_path = base_dirs.data_dir()
+            .join("Microsoft")
+            .join("Windows")
+            .join("Start Menu")
+            .join("Programs")
+            .join("Startup");
+
+        // スタートアップフォルダが存在しない場合は作成
+        if !startup_path.exists() {
+            std::fs::create_dir_all(&startup_path)
+                .context("Failed to create startup folder")?;
+        }
+
+        Ok(startup_path.join("TasktrayChime.lnk"))
+    }
+
+    fn create_shortcut(&self, shortcut_path: &std::path::Path) -> Result<()> {
+        use std::process::Command;
+
+        // PowerShellの文字列リテラル用にエスケープ
+        let escape = |s: &str| s.replace('`', "``").replace('"', "`\"").replace('$', "`$");
+        let arguments = self.args
+            .iter()
+            .map(|a| format!("\"{}\"", a.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // PowerShellを使ってショートカットを作成
+        let powershell_script = format!(
+            r#"
+            $WshShell = New-Object -ComObject WScript.Shell
+            $Shortcut = $WshShell.CreateShortcut("{}")
+            $Shortcut.TargetPath = "{}"
+            $Shortcut.Arguments = "{}"
+            $Shortcut.WorkingDirectory = "{}"
+            $Shortcut.Description = "Tasktray Chime - 時報アプリ"
+            $Shortcut.Save()
+            "#,
+            escape(&shortcut_path.to_string_lossy()),
+            escape(&self.exe_path.to_string_lossy()),
+            escape(&arguments),
+            escape(
+                &self.exe_path.parent()
+                    .unwrap_or_else(|| std::path::Path::new(""))
+                    .to_string_lossy()
+            )
+        );
+
+        let output = Command::new("powershell")
+            .args(["-Command", &powershell_script])
+            .output()
+            .context("Failed to execute PowerShell command for creating shortcut")?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to create shortcut: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl AutoStart for WindowsStartupShortcut {
+    fn is_enabled(&self) -> Result<bool> {
+        // スタートアップフォルダのショートカットの存在を確認
+        Ok(Self::shortcut_path()?.exists())
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<()> {
+        let shortcut_path = Self::shortcut_path()
+            .context("Failed to get startup folder path")?;
+
+        if enabled {
+            self.create_shortcut(&shortcut_path)
+                .context("Failed to create shortcut in startup folder")?;
+            tracing::info!("Autostart enabled - created shortcut at: {:?}", shortcut_path);
+        } else {
+            remove_if_exists(&shortcut_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Linux等: `~/.config/autostart` の XDG .desktop エントリ
+#[cfg(all(unix, not(target_os = "macos")))]
+pub struct XdgAutostart {
+    exe_path: PathBuf,
+    args: Vec<OsString>,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl XdgAutostart {
+    fn desktop_file_path() -> Result<PathBuf> {
+        let base_dirs = directories::BaseDirs::new()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get base directories"))?;
+        Ok(base_dirs.config_dir().join("autostart").join("tasktray-chime.desktop"))
+    }
+
+    /// Desktop Entry仕様に従って Exec の引数をクォート
+    fn quote_exec_arg(arg: &OsStr) -> String {
+        let mut quoted = String::from("\"");
+        for c in arg.to_string_lossy().chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        // クォート規則の後に文字列値としてのエスケープ（"\\" と "%" のフィールドコード）を適用
+        quoted.replace('\\', "\\\\").replace('%', "%%")
+    }
+
+    fn desktop_entry(&self) -> String {
+        let exec = std::iter::once(self.exe_path.as_os_str())
+            .chain(self.args.iter().map(OsString::as_os_str))
+            .map(Self::quote_exec_arg)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Tasktray Chime\n\
+             Comment=Tasktray Chime - 時報アプリ\n\
+             Exec={}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            exec
+        )
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl AutoStart for XdgAutostart {
+    fn is_enabled(&self) -> Result<bool> {
+        let path = Self::desktop_file_path()?;
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        // Hidden=true のエントリは無効扱い（デスクトップ環境の設定画面で無効化された場合）
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read autostart entry: {:?}", path))?;
+        Ok(!content.lines().any(|line| line.trim() == "Hidden=true"))
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<()> {
+        let path = Self::desktop_file_path()?;
+
+        if enabled {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create autostart directory: {:?}", parent))?;
+            }
+            std::fs::write(&path, self.desktop_entry())
+                .with_context(|| format!("Failed to write autostart entry: {:?}", path))?;
+            tracing::info!("Autostart enabled - created desktop entry at: {:?}", path);
+        } else {
+            remove_if_exists(&path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// macOS: `~/Library/LaunchAgents` の plist
+#[cfg(target_os = "macos")]
+pub struct LaunchAgent {
+    exe_path: PathBuf,
+    args: Vec<OsString>,
+}
+
+#[cfg(target_os = "macos")]
+impl LaunchAgent {
+    const LABEL: &'static str = "com.kotet.tasktray-chime";
+
+    fn plist_path() -> Result<PathBuf> {
+        let base_dirs = directories::BaseDirs::new()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get base directories"))?;
+        Ok(base_dirs.home_dir()
+            .join("Library")
+            .join("LaunchAgents")
+            .join(format!("{}.plist", Self::LABEL)))
+    }
+
+    fn escape_xml(s: &OsStr) -> String {
+        s.to_string_lossy()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn plist(&self) -> String {
+        let arguments = std::iter::once(self.exe_path.as_os_str())
+            .chain(self.args.iter().map(OsString::as_os_str))
+            .map(|a| format!("        <string>{}</string>\n", Self::escape_xml(a)))
+            .collect::<String>();
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            Self::LABEL,
+            arguments
+        )
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl AutoStart for LaunchAgent {
+    fn is_enabled(&self) -> Result<bool> {
+        Ok(Self::plist_path()?.exists())
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<()> {
+        let path = Self::plist_path()?;
+
+        if enabled {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create LaunchAgents directory: {:?}", parent))?;
+            }
+            std::fs::write(&path, self.plist())
+                .with_context(|| format!("Failed to write launch agent: {:?}", path))?;
+            tracing::info!("Autostart enabled - created launch agent at: {:?}", path);
+        } else {
+            remove_if_exists(&path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 自動起動エントリを削除（存在しない場合は何もしない）
+#[cfg(any(target_os = "windows", unix))]
+fn remove_if_exists(path: &std::path::Path) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove autostart entry: {:?}", path))?;
+        tracing::info!("Autostart disabled - removed {:?}", path);
+    } else {
+        tracing::info!("Autostart was not enabled - no entry found at {:?}", path);
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use crate::ipc::ControlCommand;
+
+/// コマンドライン引数
+#[derive(Debug, Parser)]
+#[command(name = "tasktray-chime", version, about = "Tasktray Chime - 時報アプリ")]
+pub struct Cli {
+    /// 設定ファイルのパス（省略時は TASKTRAY_CHIME_CONFIG → 実行ファイルのディレクトリ → 標準ディレクトリの順に探索）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// タスクトレイを使わずにスケジューラーと音声再生のみで動作する（サーバー・キオスク向け）
+    #[arg(long)]
+    pub headless: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 設定ファイル（includeを含む）を検証して結果を表示
+    Validate,
+    /// 設定ファイルを別の形式（拡張子で判定: .yaml/.yml/.toml/.json）に変換して書き出す
+    Export {
+        /// 出力先ファイル
+        output: PathBuf,
+        /// includeを展開して全スケジュールを1ファイルにまとめる
+        #[arg(long)]
+        merged: bool,
+        /// 出力先が既に存在する場合も上書きする
+        #[arg(long)]
+        force: bool,
+    },
+    /// 実行中のインスタンスを操作する
+    Ctl {
+        #[command(subcommand)]
+        command: ControlCommand,
+    },
+}
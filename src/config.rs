@@ -1,13 +1,39 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use anyhow::{Context, Result};
+use chrono::NaiveTime;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
+    /// 追加で読み込むスケジュールファイル（ファイルパスまたはglobパターン）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
     pub logging: LoggingConfig,
     pub audio: AudioConfig,
+    #[serde(default)]
     pub schedules: Vec<Schedule>,
     pub behavior: BehaviorConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// スケジュールイベントの送信先
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// ローカルHTTPエンドポイント（未指定時は無効）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpConfig>,
+    /// MQTT ブローカーへの接続（未指定時は無効）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mqtt: Option<MqttConfig>,
+    /// 読み込み元の設定ファイル
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
+    /// マージ時に上書きされたスケジュールの記録
+    #[serde(skip)]
+    pub overrides: Vec<ScheduleOverride>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -16,11 +42,52 @@ pub struct LoggingConfig {
     pub directory: String,
     pub rotate: bool,
     pub max_files: u32,
+    /// ログファイルの形式
+    #[serde(default, skip_serializing_if = "LogFormat::is_text")]
+    pub format: LogFormat,
+}
+
+/// ログファイルの形式
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 1行ごとのテキスト（ファイル名・行番号付き）
+    #[default]
+    Text,
+    /// 1行1オブジェクトの JSON（ログ収集ツール向け）
+    Json,
+    /// 短いテキスト
+    Compact,
+}
+
+impl LogFormat {
+    pub fn is_text(&self) -> bool {
+        *self == LogFormat::Text
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AudioConfig {
     pub global_volume: u8,
+    /// このサイズ（MB）を超える音声ファイルはメモリに読み込まずストリーミング再生する
+    #[serde(default = "default_stream_threshold_mb")]
+    pub stream_threshold_mb: u64,
+    /// 設定ファイルのディレクトリで見つからない音声ファイルを探すディレクトリ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sounds_dir: Option<String>,
+    /// すべてのスケジュールで最後に試す代替音声（ファイルまたは `builtin:<名前>`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_sound: Option<String>,
+}
+
+fn default_stream_threshold_mb() -> u64 {
+    8
+}
+
+impl AudioConfig {
+    pub fn stream_threshold_bytes(&self) -> u64 {
+        self.stream_threshold_mb.saturating_mul(1024 * 1024)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -29,60 +96,865 @@ pub struct Schedule {
     #[serde(rename = "type")]
     pub schedule_type: String,
     pub cron: String,
+    /// 音声ファイル（`delivery: notify` / `none` の場合は省略可）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub file: String,
     pub enabled: bool,
+    /// 通知方法（音声 / デスクトップ通知 / 両方）
+    #[serde(default, skip_serializing_if = "Delivery::is_sound")]
+    pub delivery: Delivery,
+    /// デスクトップ通知のタイトル・本文テンプレート
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification: Option<NotificationTemplate>,
+    /// リマインダーとして扱う（トレイからスヌーズ・スキップできる）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reminder: bool,
+    /// 再生する最大秒数（長い音声を途中で打ち切る）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_seconds: Option<u64>,
+    /// 再生を開始する位置（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<f64>,
+    /// 再生を終了する位置（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_at: Option<f64>,
+    /// 再生速度（既定 1.0。音の高さも変わる）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f32>,
+    /// 再生する回数（既定 1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_count: Option<u32>,
+    /// 再生に失敗したときに順に試す代替音声（ファイルまたは `builtin:<名前>`）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<String>,
+    /// 実行開始時に実行するコマンド
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_fire: Option<Hook>,
+    /// 再生・通知が成功した後に実行するコマンド
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_success: Option<Hook>,
+    /// 再生・通知が失敗した後に実行するコマンド
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<Hook>,
+    /// このスケジュールが定義されていたファイル
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+    /// `file` を解決した音声ファイルのパス（読み込み時に設定される）
+    #[serde(skip)]
+    pub resolved_file: Option<PathBuf>,
+    /// `fallback` と `audio.fallback_sound` を解決した代替音声（読み込み時に設定される）
+    #[serde(skip)]
+    pub resolved_fallback: Vec<FallbackSound>,
+}
+
+impl Schedule {
+    /// 再生する音声ファイルのパス（解決済みのパス、未解決の場合は `file` そのまま）
+    pub fn sound_path(&self) -> &Path {
+        self.resolved_file.as_deref().unwrap_or(Path::new(&self.file))
+    }
+}
+
+/// 代替音声
+#[derive(Debug, Clone, PartialEq)]
+pub enum FallbackSound {
+    File(PathBuf),
+    /// 組み込みの音（`builtin:<名前>`）
+    Builtin(String),
+}
+
+impl fmt::Display for FallbackSound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FallbackSound::File(path) => write!(f, "{}", path.display()),
+            FallbackSound::Builtin(name) => write!(f, "{}{}", BUILTIN_SOUND_PREFIX, name),
+        }
+    }
+}
+
+/// 組み込みの音を指定する接頭辞
+const BUILTIN_SOUND_PREFIX: &str = "builtin:";
+
+/// スケジュールの通知方法
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Delivery {
+    #[default]
+    Sound,
+    Notify,
+    Both,
+    /// 音声も通知も行わない（フックのみ実行）
+    None,
+}
+
+impl Delivery {
+    pub fn is_sound(&self) -> bool {
+        *self == Delivery::Sound
+    }
+
+    /// 音声を再生するか
+    pub fn plays_sound(self) -> bool {
+        matches!(self, Delivery::Sound | Delivery::Both)
+    }
+
+    /// デスクトップ通知を表示するか
+    pub fn notifies(self) -> bool {
+        matches!(self, Delivery::Notify | Delivery::Both)
+    }
+}
+
+/// デスクトップ通知のテンプレート
+///
+/// `{id}`・`{time}`（HH:MM）・`{date}`（YYYY-MM-DD）が実行時の値に置き換えられる
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+pub struct NotificationTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BehaviorConfig {
     pub retry_on_fail: u32,
     pub retry_delay_seconds: u64,
+    /// 静音時間帯（この間はスケジュールを実行しない）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+    /// 終了時に再生中の音声・通知の完了を待つ最大秒数
+    #[serde(default = "default_shutdown_grace_seconds")]
+    pub shutdown_grace_seconds: u64,
+}
+
+fn default_shutdown_grace_seconds() -> u64 {
+    5
+}
+
+/// 静音時間帯（"HH:MM" 形式、終了が開始より前なら日付をまたぐ）
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// スケジュールの実行に合わせて実行するコマンド（OS標準のシェル経由）
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Hook {
+    pub command: String,
+    /// この秒数を過ぎても終了しない場合は強制終了する
+    #[serde(default = "default_hook_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_hook_timeout_seconds() -> u64 {
+    30
+}
+
+/// Webhook で送信できるイベントの種類（`ScheduleOutcome` に対応）
+pub const WEBHOOK_EVENTS: [&str; 4] = ["fired", "succeeded", "failed", "skipped"];
+
+/// スケジュールイベントを JSON で POST する送信先
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// 送信するイベント（未指定時はすべて）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// 追加のリクエストヘッダー（認証トークンなど）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// 1回の送信のタイムアウト（秒）
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// 送信に失敗した場合の再送回数
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+fn default_webhook_timeout_seconds() -> u64 {
+    5
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+impl WebhookConfig {
+    /// 指定した種類のイベントを送信するか
+    pub fn accepts(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+/// 127.0.0.1 で待ち受けるHTTPエンドポイント（スケジュールの実行・音声の再生）
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct HttpConfig {
+    #[serde(default = "default_http_port")]
+    pub port: u16,
+    /// 指定時は `Authorization: Bearer <token>` を要求する
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn default_http_port() -> u16 {
+    7878
+}
+
+/// MQTT ブローカーへの接続（イベントの送信と操作の受信）
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// トピックの先頭（`<prefix>/status`、`<prefix>/events/<ID>`、`<prefix>/command`）
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+    #[serde(default = "default_mqtt_keep_alive_seconds")]
+    pub keep_alive_seconds: u64,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "tasktray-chime".to_string()
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "tasktray-chime".to_string()
+}
+
+fn default_mqtt_keep_alive_seconds() -> u64 {
+    30
+}
+
+/// "HH:MM" 形式の時刻を解析
+pub fn parse_time_of_day(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .with_context(|| format!("Invalid time '{}' (expected HH:MM)", value))
+}
+
+impl QuietHours {
+    /// 指定時刻が静音時間帯に含まれるか（設定が不正な場合は false）
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time_of_day(&self.start), parse_time_of_day(&self.end)) else {
+            return false;
+        };
+
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        parse_time_of_day(&self.start)?;
+        parse_time_of_day(&self.end)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct UiConfig {
+    /// 設定ファイルを開くエディタ（未指定時は $VISUAL → $EDITOR → OS標準）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// 表示言語（"ja", "en" など。未指定または "auto" でOSのロケールから判定）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// トレイの「一時停止」に表示する再開時刻（"HH:MM"、未指定時は 09:00）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pause_until: Vec<String>,
+}
+
+impl UiConfig {
+    /// 一時停止の再開時刻の候補（不正な値は除外）
+    pub fn pause_until_times(&self) -> Vec<NaiveTime> {
+        if self.pause_until.is_empty() {
+            return vec![NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default()];
+        }
+        self.pause_until
+            .iter()
+            .filter_map(|value| parse_time_of_day(value).ok())
+            .collect()
+    }
+}
+
+/// 設定ファイルの形式（拡張子で判定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// 拡張子から形式を判定（.yaml / .yml / .toml / .json）
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match ext.as_str() {
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!(
+                "Unsupported config file extension: {:?} (expected .yaml, .yml, .toml or .json)",
+                path
+            )),
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T> {
+        let value = match self {
+            Self::Yaml => serde_yaml::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+            Self::Json => serde_json::from_str(content)?,
+        };
+        Ok(value)
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> Result<String> {
+        let content = match self {
+            Self::Yaml => serde_yaml::to_string(value)?,
+            Self::Toml => toml::to_string_pretty(value)?,
+            Self::Json => serde_json::to_string_pretty(value)? + "\n",
+        };
+        Ok(content)
+    }
+}
+
+/// `include` で読み込むスケジュールパック
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchedulePack {
+    #[serde(default)]
+    schedules: Vec<Schedule>,
+}
+
+/// 同じIDのスケジュールが後から読み込まれたファイルで置き換えられた記録
+#[derive(Debug, Clone)]
+pub struct ScheduleOverride {
+    pub id: String,
+    pub replaced: Option<PathBuf>,
+    pub by: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub schedule_id: Option<String>,
+    pub source: Option<PathBuf>,
+    pub message: String,
+}
+
+/// 設定ファイルの検証結果
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub config_path: Option<PathBuf>,
+    pub schedules: Vec<Schedule>,
+    pub overrides: Vec<ScheduleOverride>,
+    pub issues: Vec<ValidationIssue>,
 }
 
 impl Config {
+    /// 設定ファイルを読み込み、`include` で指定されたスケジュールをマージする
+    ///
+    /// マージ規則:
+    /// - `include` の各エントリを記述順に読み込む（globの一致結果はパス順）
+    /// - 同じIDのスケジュールは後から読み込まれた方で置き換える
+    /// - 設定ファイル本体の `schedules` は最後に適用され、常にincludeより優先される
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = std::fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read config file: {:?}", path.as_ref()))?;
-        
-        let config: Config = serde_yaml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {:?}", path.as_ref()))?;
-        
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+
+        let mut config: Config = ConfigFormat::from_path(path)?
+            .parse(&content)
+            .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+
+        config.source_path = Some(path.to_path_buf());
+        for schedule in &mut config.schedules {
+            schedule.source = Some(path.to_path_buf());
+        }
+        config.merge_includes()?;
+        config.resolve_sound_paths();
+
         Ok(config)
     }
 
+    /// 設定ファイルを保存（includeから読み込んだスケジュールは書き出さない）
+    ///
+    /// 形式は保存先の拡張子で決まる
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let content = serde_yaml::to_string(self)
-            .context("Failed to serialize config to YAML")?;
-        
+        let mut own = self.clone();
+        own.schedules.retain(|s| s.source.is_none() || s.source == self.source_path);
+
+        let format = ConfigFormat::from_path(path.as_ref())?;
+        let content = format.serialize(&own)
+            .with_context(|| format!("Failed to serialize config as {:?}", format))?;
+
         std::fs::write(&path, content)
             .with_context(|| format!("Failed to write config file: {:?}", path.as_ref()))?;
-        
+
         Ok(())
     }
 
-    /// デフォルトの設定を作成
-    pub fn default() -> Self {
-        // 実行ファイルと同じディレクトリ配下のlogsディレクトリをデフォルトとする
-        let default_log_dir = if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_dir) = exe_path.parent() {
-                exe_dir.join("logs").to_string_lossy().to_string()
-            } else {
-                "./logs".to_string()
+    /// `include` のファイルを読み込み、スケジュールをID単位でマージ
+    fn merge_includes(&mut self) -> Result<()> {
+        let base_dir = self.config_dir();
+
+        let own_schedules = std::mem::take(&mut self.schedules);
+        let mut merged: Vec<Schedule> = Vec::new();
+
+        for pattern in &self.include {
+            for file in Self::expand_include(&base_dir, pattern)? {
+                let content = std::fs::read_to_string(&file)
+                    .with_context(|| format!("Failed to read included file: {:?}", file))?;
+                let pack: SchedulePack = ConfigFormat::from_path(&file)?
+                    .parse(&content)
+                    .with_context(|| format!("Failed to parse included file: {:?}", file))?;
+
+                tracing::info!("Loaded {} schedule(s) from included file {:?}", pack.schedules.len(), file);
+                for mut schedule in pack.schedules {
+                    schedule.source = Some(file.clone());
+                    Self::merge_schedule(&mut merged, &mut self.overrides, schedule);
+                }
+            }
+        }
+
+        for schedule in own_schedules {
+            Self::merge_schedule(&mut merged, &mut self.overrides, schedule);
+        }
+
+        self.schedules = merged;
+        Ok(())
+    }
+
+    /// includeのパターンを実際のファイル一覧に展開
+    fn expand_include(base_dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+        let is_glob = pattern.contains(['*', '?', '[']);
+
+        if !is_glob {
+            let file = base_dir.join(pattern);
+            if !file.exists() {
+                return Err(anyhow::anyhow!("Included file not found: {:?}", file));
             }
+            return Ok(vec![file]);
+        }
+
+        // 相対パターンは設定ファイルのディレクトリを基準とする
+        let full_pattern = if Path::new(pattern).is_absolute() {
+            pattern.to_string()
         } else {
-            // フォールバック: カレントディレクトリ
-            "./logs".to_string()
+            let escaped_base = glob::Pattern::escape(&base_dir.to_string_lossy());
+            if escaped_base.is_empty() {
+                pattern.to_string()
+            } else {
+                format!("{}/{}", escaped_base, pattern)
+            }
         };
 
+        let mut files = glob::glob(&full_pattern)
+            .with_context(|| format!("Invalid include pattern: {}", pattern))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .with_context(|| format!("Failed to expand include pattern: {}", pattern))?;
+        files.sort();
+
+        if files.is_empty() {
+            tracing::warn!("Include pattern '{}' did not match any files", pattern);
+        }
+
+        Ok(files)
+    }
+
+    /// 同じIDが既にあれば置き換え、なければ末尾に追加
+    fn merge_schedule(merged: &mut Vec<Schedule>, overrides: &mut Vec<ScheduleOverride>, schedule: Schedule) {
+        match merged.iter_mut().find(|s| s.id == schedule.id) {
+            Some(existing) => {
+                tracing::info!(
+                    schedule_id = %schedule.id,
+                    source = ?schedule.source,
+                    replaced = ?existing.source,
+                    "Schedule overrides an earlier one with the same id"
+                );
+                overrides.push(ScheduleOverride {
+                    id: schedule.id.clone(),
+                    replaced: existing.source.clone(),
+                    by: schedule.source.clone(),
+                });
+                *existing = schedule;
+            }
+            None => merged.push(schedule),
+        }
+    }
+
+    /// スケジュールの有効/無効を変更
+    ///
+    /// includeから読み込んだスケジュールは設定ファイル本体での上書きとして保存される
+    pub fn set_schedule_enabled(&mut self, id: &str, enabled: bool) -> Result<()> {
+        let source_path = self.source_path.clone();
+        let schedule = self.schedules
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown schedule: {}", id))?;
+
+        schedule.enabled = enabled;
+        if schedule.source != source_path {
+            tracing::info!(schedule_id = id, source = ?schedule.source, "Schedule will be saved as an override in the main config");
+            schedule.source = source_path;
+        }
+        Ok(())
+    }
+
+    /// 別の形式で書き出す
+    ///
+    /// `merged` が true の場合はincludeを展開し、全スケジュールを1ファイルにまとめる
+    pub fn export_to_file<P: AsRef<Path>>(&self, path: P, merged: bool) -> Result<()> {
+        if !merged {
+            return self.save_to_file(path);
+        }
+
+        let mut flattened = self.clone();
+        flattened.include.clear();
+        for schedule in &mut flattened.schedules {
+            schedule.source = None;
+        }
+        flattened.save_to_file(path)
+    }
+
+    /// 音声ファイルのパスを解決
+    ///
+    /// `~` と環境変数（`$VAR` / `${VAR}`）を展開し、相対パスは設定ファイルのディレクトリ、`audio.sounds_dir` の順に探す
+    /// どちらにも見つからない場合は設定ファイルのディレクトリ基準のパスを返す
+    pub fn resolve_sound_path(&self, file: &str) -> PathBuf {
+        let path = expand_path(file);
+        if path.is_absolute() {
+            return path;
+        }
+
+        let base_dir = self.config_dir();
+        let candidate = absolute_path(&base_dir.join(&path));
+        if candidate.exists() {
+            return candidate;
+        }
+        if let Some(sounds_dir) = &self.audio.sounds_dir {
+            let found = absolute_path(&base_dir.join(expand_path(sounds_dir)).join(&path));
+            if found.exists() {
+                return found;
+            }
+        }
+        candidate
+    }
+
+    /// 代替音声を解決（`builtin:<名前>` 以外は音声ファイルとしてパスを解決する）
+    pub fn resolve_fallback_sound(&self, value: &str) -> FallbackSound {
+        match value.strip_prefix(BUILTIN_SOUND_PREFIX) {
+            Some(name) => FallbackSound::Builtin(name.to_string()),
+            None => FallbackSound::File(self.resolve_sound_path(value)),
+        }
+    }
+
+    /// 全スケジュールの音声ファイルと代替音声のパスを解決
+    ///
+    /// `audio.fallback_sound` は各スケジュールの `fallback` の後に追加する
+    fn resolve_sound_paths(&mut self) {
+        let global_fallback = self.audio.fallback_sound.as_deref().map(|value| self.resolve_fallback_sound(value));
+        let resolved: Vec<(Option<PathBuf>, Vec<FallbackSound>)> = self.schedules
+            .iter()
+            .map(|schedule| {
+                let file = (!schedule.file.is_empty()).then(|| self.resolve_sound_path(&schedule.file));
+                let mut fallback: Vec<FallbackSound> = schedule.fallback.iter().map(|value| self.resolve_fallback_sound(value)).collect();
+                if let Some(global) = &global_fallback
+                    && !fallback.contains(global)
+                {
+                    fallback.push(global.clone());
+                }
+                (file, fallback)
+            })
+            .collect();
+        for (schedule, (path, fallback)) in self.schedules.iter_mut().zip(resolved) {
+            if let Some(path) = &path {
+                tracing::info!(schedule_id = %schedule.id, file = %schedule.file, resolved = %path.display(), "Resolved audio file");
+            }
+            schedule.resolved_file = path;
+            schedule.resolved_fallback = fallback;
+        }
+    }
+
+    /// 設定ファイルのディレクトリ（読み込み元がない場合はカレントディレクトリ）
+    fn config_dir(&self) -> PathBuf {
+        self.source_path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+
+    /// ユーザーのメッセージカタログを置くディレクトリ（設定ファイルと同じ場所の `locales`）
+    pub fn locales_dir(&self) -> Option<PathBuf> {
+        self.source_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.join("locales"))
+    }
+
+    /// 設定内容を検証し、スケジュールごとの読み込み元と問題点をまとめる
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+
+        if self.audio.global_volume > 100 {
+            issues.push(ValidationIssue {
+                severity: Severity::Warning,
+                schedule_id: None,
+                source: self.source_path.clone(),
+                message: format!("audio.global_volume is {} (expected 0-100)", self.audio.global_volume),
+            });
+        }
+
+        if let Some(fallback) = &self.audio.fallback_sound {
+            let issue = match self.resolve_fallback_sound(fallback) {
+                FallbackSound::Builtin(name) if !crate::audio::BUILTIN_SOUNDS.contains(&name.as_str()) => Some((
+                    Severity::Error,
+                    format!("audio.fallback_sound: unknown builtin sound '{}' (expected one of {})", name, crate::audio::BUILTIN_SOUNDS.join(", ")),
+                )),
+                FallbackSound::File(path) if !path.exists() => Some((
+                    Severity::Warning,
+                    format!("audio.fallback_sound: audio file not found: {} ({})", fallback, path.display()),
+                )),
+                _ => None,
+            };
+            if let Some((severity, message)) = issue {
+                issues.push(ValidationIssue {
+                    severity,
+                    schedule_id: None,
+                    source: self.source_path.clone(),
+                    message,
+                });
+            }
+        }
+
+        if let Some(quiet_hours) = &self.behavior.quiet_hours
+            && let Err(e) = quiet_hours.validate()
+        {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                schedule_id: None,
+                source: self.source_path.clone(),
+                message: format!("behavior.quiet_hours: {:#}", e),
+            });
+        }
+
+        if let Some(language) = &self.ui.language
+            && !language.eq_ignore_ascii_case("auto")
+            && !crate::i18n::is_available(&crate::i18n::normalize_language(language), self.locales_dir().as_deref())
+        {
+            issues.push(ValidationIssue {
+                severity: Severity::Warning,
+                schedule_id: None,
+                source: self.source_path.clone(),
+                message: format!("ui.language: no message catalog for '{}', English is used", language),
+            });
+        }
+
+        for value in &self.ui.pause_until {
+            if let Err(e) = parse_time_of_day(value) {
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    schedule_id: None,
+                    source: self.source_path.clone(),
+                    message: format!("ui.pause_until: {:#}", e),
+                });
+            }
+        }
+
+        for (index, webhook) in self.webhooks.iter().enumerate() {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    schedule_id: None,
+                    source: self.source_path.clone(),
+                    message: format!("webhooks[{}].url must start with http:// or https:// ({})", index, webhook.url),
+                });
+            }
+            for event in webhook.events.iter().filter(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+                issues.push(ValidationIssue {
+                    severity: Severity::Warning,
+                    schedule_id: None,
+                    source: self.source_path.clone(),
+                    message: format!("webhooks[{}].events: unknown event '{}' (expected one of {})", index, event, WEBHOOK_EVENTS.join(", ")),
+                });
+            }
+        }
+
+        if let Some(http) = &self.http
+            && http.port == 0
+        {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                schedule_id: None,
+                source: self.source_path.clone(),
+                message: "http.port must not be 0".to_string(),
+            });
+        }
+
+        if let Some(mqtt) = &self.mqtt {
+            let mut push = |message: String| {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    schedule_id: None,
+                    source: self.source_path.clone(),
+                    message,
+                });
+            };
+            if mqtt.host.trim().is_empty() {
+                push("mqtt.host must not be empty".to_string());
+            }
+            if mqtt.client_id.trim().is_empty() {
+                push("mqtt.client_id must not be empty".to_string());
+            }
+            if mqtt.topic_prefix.contains(['+', '#']) {
+                push(format!("mqtt.topic_prefix must not contain wildcards ({})", mqtt.topic_prefix));
+            }
+        }
+
+        for ov in &self.overrides {
+            if ov.replaced == ov.by {
+                issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    schedule_id: Some(ov.id.clone()),
+                    source: ov.by.clone(),
+                    message: "duplicate schedule id in the same file".to_string(),
+                });
+            }
+        }
+
+        for schedule in &self.schedules {
+            let mut push = |severity, message: String| {
+                issues.push(ValidationIssue {
+                    severity,
+                    schedule_id: Some(schedule.id.clone()),
+                    source: schedule.source.clone(),
+                    message,
+                });
+            };
+
+            if schedule.schedule_type != "cron" {
+                push(Severity::Error, format!("unsupported schedule type '{}'", schedule.schedule_type));
+            }
+            if let Err(e) = cron::Schedule::from_str(&schedule.cron) {
+                push(Severity::Error, format!("invalid cron expression '{}': {}", schedule.cron, e));
+            }
+            if schedule.delivery.plays_sound() {
+                if schedule.file.is_empty() {
+                    push(Severity::Error, "file is required unless delivery is 'notify' or 'none'".to_string());
+                } else if !schedule.sound_path().exists() {
+                    push(Severity::Warning, format!("audio file not found: {} ({})", schedule.file, schedule.sound_path().display()));
+                }
+            }
+            for fallback in &schedule.fallback {
+                match self.resolve_fallback_sound(fallback) {
+                    FallbackSound::Builtin(name) if !crate::audio::BUILTIN_SOUNDS.contains(&name.as_str()) => {
+                        push(Severity::Error, format!("fallback: unknown builtin sound '{}' (expected one of {})", name, crate::audio::BUILTIN_SOUNDS.join(", ")));
+                    }
+                    FallbackSound::File(path) if !path.exists() => {
+                        push(Severity::Warning, format!("fallback audio file not found: {} ({})", fallback, path.display()));
+                    }
+                    _ => {}
+                }
+            }
+            if schedule.max_duration_seconds == Some(0) {
+                push(Severity::Error, "max_duration_seconds must be greater than 0".to_string());
+            }
+            for (name, position) in [("start_at", schedule.start_at), ("end_at", schedule.end_at)] {
+                if let Some(position) = position
+                    && !(position.is_finite() && position >= 0.0)
+                {
+                    push(Severity::Error, format!("{} must be a non-negative number of seconds", name));
+                }
+            }
+            if let Some(end_at) = schedule.end_at
+                && end_at <= schedule.start_at.unwrap_or(0.0)
+            {
+                push(Severity::Error, "end_at must be after start_at".to_string());
+            }
+            if let Some(speed) = schedule.speed
+                && !(speed.is_finite() && speed > 0.0)
+            {
+                push(Severity::Error, "speed must be greater than 0".to_string());
+            }
+            if schedule.loop_count == Some(0) {
+                push(Severity::Error, "loop_count must be greater than 0".to_string());
+            }
+            let playback_options = [
+                ("max_duration_seconds", schedule.max_duration_seconds.is_some()),
+                ("start_at", schedule.start_at.is_some()),
+                ("end_at", schedule.end_at.is_some()),
+                ("speed", schedule.speed.is_some()),
+                ("loop_count", schedule.loop_count.is_some()),
+                ("fallback", !schedule.fallback.is_empty()),
+            ];
+            let ignored: Vec<&str> = playback_options.iter().filter(|(_, set)| *set).map(|(name, _)| *name).collect();
+            if !ignored.is_empty() && !schedule.delivery.plays_sound() {
+                push(Severity::Warning, format!("playback options ({}) are ignored because delivery does not include 'sound'", ignored.join(", ")));
+            }
+            let hooks = [("on_fire", &schedule.on_fire), ("on_success", &schedule.on_success), ("on_failure", &schedule.on_failure)];
+            for (name, hook) in hooks {
+                if let Some(hook) = hook
+                    && hook.command.trim().is_empty()
+                {
+                    push(Severity::Error, format!("{}.command must not be empty", name));
+                }
+            }
+            if schedule.delivery == Delivery::None && hooks.iter().all(|(_, hook)| hook.is_none()) {
+                push(Severity::Warning, "delivery is 'none' and no hook is set, the schedule does nothing".to_string());
+            }
+            if schedule.notification.is_some() && !schedule.delivery.notifies() {
+                push(Severity::Warning, "notification is ignored because delivery does not include 'notify'".to_string());
+            }
+        }
+
+        ValidationReport {
+            config_path: self.source_path.clone(),
+            schedules: self.schedules.clone(),
+            overrides: self.overrides.clone(),
+            issues,
+        }
+    }
+
+    /// デフォルトの設定を作成
+    pub fn default() -> Self {
         Self {
+            include: Vec::new(),
             logging: LoggingConfig {
                 level: "info".to_string(),
-                directory: default_log_dir,
+                // 相対パスはデータディレクトリ基準で解決される
+                directory: "logs".to_string(),
                 rotate: true,
                 max_files: 7,
+                format: LogFormat::Text,
             },
             audio: AudioConfig {
                 global_volume: 80,
+                stream_threshold_mb: default_stream_threshold_mb(),
+                sounds_dir: None,
+                fallback_sound: None,
             },
             schedules: vec![
                 Schedule {
@@ -91,12 +963,35 @@ impl Config {
                     cron: "0 * * * *".to_string(), // 毎時0分
                     file: "./audios/chime.wav".to_string(),
                     enabled: true,
+                    delivery: Delivery::Sound,
+                    notification: None,
+                    reminder: false,
+                    max_duration_seconds: None,
+                    start_at: None,
+                    end_at: None,
+                    speed: None,
+                    loop_count: None,
+                    fallback: Vec::new(),
+                    on_fire: None,
+                    on_success: None,
+                    on_failure: None,
+                    source: None,
+                    resolved_file: None,
+                    resolved_fallback: Vec::new(),
                 }
             ],
             behavior: BehaviorConfig {
                 retry_on_fail: 0,
                 retry_delay_seconds: 5,
+                quiet_hours: None,
+                shutdown_grace_seconds: default_shutdown_grace_seconds(),
             },
+            ui: UiConfig::default(),
+            webhooks: Vec::new(),
+            http: None,
+            mqtt: None,
+            source_path: None,
+            overrides: Vec::new(),
         }
     }
 
@@ -105,11 +1000,559 @@ impl Config {
         if path.as_ref().exists() {
             Self::load_from_file(&path)
         } else {
-            let config = Self::default();
+            if let Some(parent) = path.as_ref().parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create config directory: {:?}", parent))?;
+            }
+            let mut config = Self::default();
             config.save_to_file(&path)
                 .context("Failed to create default config file")?;
+            config.source_path = Some(path.as_ref().to_path_buf());
+            config.resolve_sound_paths();
             tracing::info!("Created default config file at {:?}", path.as_ref());
             Ok(config)
         }
     }
-}
\ No newline at end of file
+}
+
+impl ValidationReport {
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning).count()
+    }
+}
+
+/// `~` と環境変数を展開（展開できない場合はそのまま）
+fn expand_path(value: &str) -> PathBuf {
+    match shellexpand::full(value) {
+        Ok(expanded) => PathBuf::from(expanded.as_ref()),
+        Err(e) => {
+            tracing::warn!("Failed to expand path '{}': {}", value, e);
+            PathBuf::from(value)
+        }
+    }
+}
+
+/// 絶対パスに変換（変換できない場合はそのまま）
+fn absolute_path(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn display_source(source: &Option<PathBuf>) -> String {
+    source
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "<default>".to_string())
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Config: {}", display_source(&self.config_path))?;
+
+        writeln!(f, "Schedules ({}):", self.schedules.len())?;
+        for schedule in &self.schedules {
+            writeln!(
+                f,
+                "  {} [{}] cron=\"{}\" file={} (from {})",
+                schedule.id,
+                if schedule.enabled { "enabled" } else { "disabled" },
+                schedule.cron,
+                schedule.sound_path().display(),
+                display_source(&schedule.source)
+            )?;
+        }
+
+        let overridden: Vec<_> = self.overrides.iter().filter(|o| o.replaced != o.by).collect();
+        if !overridden.is_empty() {
+            writeln!(f, "Overrides:")?;
+            for ov in overridden {
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    ov.id,
+                    display_source(&ov.replaced),
+                    display_source(&ov.by)
+                )?;
+            }
+        }
+
+        if !self.issues.is_empty() {
+            writeln!(f, "Issues:")?;
+            for issue in &self.issues {
+                let level = match issue.severity {
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                };
+                match &issue.schedule_id {
+                    Some(id) => writeln!(
+                        f,
+                        "  {}: [{}] {} ({})",
+                        level, id, issue.message, display_source(&issue.source)
+                    )?,
+                    None => writeln!(
+                        f,
+                        "  {}: {} ({})",
+                        level, issue.message, display_source(&issue.source)
+                    )?,
+                }
+            }
+        }
+
+        write!(
+            f,
+            "{} error(s), {} warning(s)",
+            self.error_count(),
+            self.warning_count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTENSIONS: [&str; 4] = ["yaml", "yml", "toml", "json"];
+
+    /// `include`・Webhook・フック・代替音声などを使う設定
+    fn full_config() -> Config {
+        let mut config = Config::default();
+        config.include = vec!["schedules.d/*.yaml".to_string()];
+        config.logging.format = LogFormat::Json;
+        config.audio.sounds_dir = Some("~/Music/chimes".to_string());
+        config.audio.fallback_sound = Some("builtin:beep".to_string());
+
+        let schedule = &mut config.schedules[0];
+        schedule.delivery = Delivery::Both;
+        schedule.notification = Some(NotificationTemplate {
+            title: Some("Tasktray Chime".to_string()),
+            body: None,
+        });
+        schedule.reminder = true;
+        schedule.max_duration_seconds = Some(60);
+        schedule.start_at = Some(1.5);
+        schedule.speed = Some(1.25);
+        schedule.fallback = vec!["audios/bell.wav".to_string(), "builtin:beep".to_string()];
+        schedule.on_fire = Some(Hook {
+            command: "echo $TASKTRAY_CHIME_SCHEDULE_ID".to_string(),
+            timeout_seconds: 10,
+        });
+        schedule.on_failure = Some(Hook {
+            command: "notify-send failed".to_string(),
+            timeout_seconds: 30,
+        });
+        let mut second = config.schedules[0].clone();
+        second.id = "standup".to_string();
+        second.file = String::new();
+        second.delivery = Delivery::Notify;
+        second.fallback.clear();
+        second.on_fire = None;
+        config.schedules.push(second);
+
+        config.behavior.quiet_hours = Some(QuietHours {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        });
+        config.ui.editor = Some("code --wait".to_string());
+        config.ui.pause_until = vec!["09:00".to_string(), "13:00".to_string()];
+        config.webhooks = vec![
+            WebhookConfig {
+                url: "https://chat.example.com/hooks/chime".to_string(),
+                events: vec!["succeeded".to_string(), "failed".to_string()],
+                headers: BTreeMap::from([("X-Api-Key".to_string(), "secret".to_string())]),
+                timeout_seconds: 5,
+                retries: 3,
+            },
+            WebhookConfig {
+                url: "http://localhost:9000/chime".to_string(),
+                events: Vec::new(),
+                headers: BTreeMap::new(),
+                timeout_seconds: 1,
+                retries: 0,
+            },
+        ];
+        config.http = Some(HttpConfig {
+            port: 7878,
+            token: Some("change-me".to_string()),
+        });
+        config.mqtt = Some(MqttConfig {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "tasktray-chime".to_string(),
+            username: Some("chime".to_string()),
+            password: None,
+            topic_prefix: "office/chime".to_string(),
+            keep_alive_seconds: 30,
+        });
+        config
+    }
+
+    /// 保存される内容の比較用（読み込み時に設定される項目は含まない）
+    fn saved_value(config: &Config) -> serde_json::Value {
+        serde_json::to_value(config).unwrap()
+    }
+
+    /// 保存して読み込み直した設定（読み込み元のファイルのスケジュールのみ）
+    fn round_trip(config: &Config, path: &Path) -> Config {
+        config.save_to_file(path).unwrap_or_else(|e| panic!("failed to save {:?}: {:#}", path, e));
+        let mut loaded = Config::load_from_file(path).unwrap_or_else(|e| panic!("failed to load {:?}: {:#}", path, e));
+        loaded.schedules.retain(|s| s.source.as_deref() == Some(path));
+        loaded
+    }
+
+    #[test]
+    fn default_config_round_trips_in_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        for ext in EXTENSIONS {
+            let path = dir.path().join(format!("config.{}", ext));
+            let loaded = round_trip(&config, &path);
+            assert_eq!(saved_value(&loaded), saved_value(&config), "{}", ext);
+        }
+    }
+
+    #[test]
+    fn full_config_round_trips_in_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("schedules.d")).unwrap();
+        std::fs::write(
+            dir.path().join("schedules.d/shared.yaml"),
+            "schedules:\n  - id: shared_chime\n    type: cron\n    cron: \"0 0 12 * * *\"\n    file: audios/noon.wav\n    enabled: true\n",
+        )
+        .unwrap();
+
+        let config = full_config();
+        for ext in EXTENSIONS {
+            let path = dir.path().join(format!("config.{}", ext));
+            let loaded = round_trip(&config, &path);
+            assert_eq!(saved_value(&loaded), saved_value(&config), "{}", ext);
+
+            // include のスケジュールは読み込み時にマージされ、保存時には書き出されない
+            let merged = Config::load_from_file(&path).unwrap();
+            assert!(merged.schedules.iter().any(|s| s.id == "shared_chime"), "{}", ext);
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert!(!content.contains("shared_chime"), "{}", ext);
+        }
+    }
+
+    #[test]
+    fn converts_between_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("schedules.d")).unwrap();
+        let config = full_config();
+
+        let yaml = dir.path().join("config.yaml");
+        config.save_to_file(&yaml).unwrap();
+        let from_yaml = Config::load_from_file(&yaml).unwrap();
+        let toml = dir.path().join("config.toml");
+        from_yaml.export_to_file(&toml, false).unwrap();
+        let json = dir.path().join("config.json");
+        Config::load_from_file(&toml).unwrap().export_to_file(&json, false).unwrap();
+
+        assert_eq!(saved_value(&Config::load_from_file(&json).unwrap()), saved_value(&config));
+    }
+
+    #[test]
+    fn rejects_unknown_extension() {
+        assert!(ConfigFormat::from_path(Path::new("config.ini")).is_err());
+        assert_eq!(ConfigFormat::from_path(Path::new("CONFIG.YML")).unwrap(), ConfigFormat::Yaml);
+    }
+
+    const HAND_WRITTEN_TOML: &str = r#"
+[logging]
+level = "debug"
+directory = "./logs"
+rotate = false
+max_files = 3
+
+[audio]
+global_volume = 60
+
+[behavior]
+retry_on_fail = 2
+retry_delay_seconds = 10
+
+[[schedules]]
+id = "hourly_chime"
+type = "cron"
+cron = "0 0 * * * *"
+file = "audios/chime.wav"
+enabled = true
+speed = 1.5
+fallback = ["builtin:beep"]
+
+[[schedules]]
+id = "stand_up"
+type = "cron"
+cron = "0 30 9 * * 1-5"
+enabled = false
+delivery = "notify"
+reminder = true
+
+[schedules.notification]
+title = "Stand-up"
+
+[schedules.on_success]
+command = "echo done"
+"#;
+
+    const HAND_WRITTEN_JSON: &str = r#"{
+  "logging": { "level": "debug", "directory": "./logs", "rotate": false, "max_files": 3 },
+  "audio": { "global_volume": 60 },
+  "behavior": { "retry_on_fail": 2, "retry_delay_seconds": 10 },
+  "schedules": [
+    {
+      "id": "hourly_chime",
+      "type": "cron",
+      "cron": "0 0 * * * *",
+      "file": "audios/chime.wav",
+      "enabled": true,
+      "speed": 1.5,
+      "fallback": ["builtin:beep"]
+    },
+    {
+      "id": "stand_up",
+      "type": "cron",
+      "cron": "0 30 9 * * 1-5",
+      "enabled": false,
+      "delivery": "notify",
+      "reminder": true,
+      "notification": { "title": "Stand-up" },
+      "on_success": { "command": "echo done" }
+    }
+  ]
+}"#;
+
+    #[test]
+    fn parses_hand_written_toml_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in [("config.toml", HAND_WRITTEN_TOML), ("config.json", HAND_WRITTEN_JSON)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            let config = Config::load_from_file(&path).unwrap();
+
+            assert_eq!(config.logging.level, "debug", "{}", name);
+            assert_eq!(config.audio.global_volume, 60, "{}", name);
+            // 省略した項目は既定値
+            assert_eq!(config.audio.stream_threshold_mb, 8, "{}", name);
+            assert_eq!(config.behavior.shutdown_grace_seconds, 5, "{}", name);
+            assert_eq!(ids(&config), ["hourly_chime", "stand_up"], "{}", name);
+
+            let chime = schedule(&config, "hourly_chime");
+            assert_eq!((chime.cron.as_str(), chime.enabled, chime.delivery), ("0 0 * * * *", true, Delivery::Sound), "{}", name);
+            assert_eq!(chime.speed, Some(1.5), "{}", name);
+            assert_eq!(chime.resolved_file.as_deref(), Some(dir.path().join("audios/chime.wav").as_path()), "{}", name);
+            assert_eq!(chime.resolved_fallback, [FallbackSound::Builtin("beep".to_string())], "{}", name);
+            assert_eq!(chime.source.as_deref(), Some(path.as_path()), "{}", name);
+
+            let stand_up = schedule(&config, "stand_up");
+            assert_eq!((stand_up.cron.as_str(), stand_up.enabled, stand_up.delivery), ("0 30 9 * * 1-5", false, Delivery::Notify), "{}", name);
+            assert!(stand_up.reminder && stand_up.file.is_empty() && stand_up.resolved_file.is_none(), "{}", name);
+            assert_eq!(stand_up.notification.as_ref().and_then(|n| n.title.as_deref()), Some("Stand-up"), "{}", name);
+            let hook = stand_up.on_success.as_ref().unwrap();
+            assert_eq!((hook.command.as_str(), hook.timeout_seconds), ("echo done", 30), "{}", name);
+        }
+    }
+
+    #[test]
+    fn load_rejects_unsupported_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["config.ini", "config"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, HAND_WRITTEN_JSON).unwrap();
+            let error = Config::load_from_file(&path).unwrap_err();
+            assert!(error.to_string().starts_with("Unsupported config file extension"), "{}: {:#}", name, error);
+        }
+    }
+
+    /// スケジュールパックを書き出す（`file` には読み込み元の区別のために `<id>-<名前>.wav` を入れる）
+    fn write_pack(dir: &Path, name: &str, ids: &[&str]) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let stem = Path::new(name).file_stem().unwrap().to_string_lossy();
+        let schedules: String = ids
+            .iter()
+            .map(|id| format!("  - id: {}\n    type: cron\n    cron: \"0 0 * * * *\"\n    file: {}-{}.wav\n    enabled: true\n", id, id, stem))
+            .collect();
+        std::fs::write(&path, format!("schedules:\n{}", schedules)).unwrap();
+        path
+    }
+
+    /// `include` と本体のスケジュールを指定した設定ファイルを書き出して読み込む
+    fn load_with_includes(dir: &Path, include: &[&str], own_ids: &[&str]) -> Config {
+        let mut config = Config::default();
+        config.include = include.iter().map(|s| s.to_string()).collect();
+        let template = config.schedules[0].clone();
+        config.schedules = own_ids
+            .iter()
+            .map(|id| Schedule { id: id.to_string(), file: format!("{}-main.wav", id), ..template.clone() })
+            .collect();
+        let path = dir.join("config.yaml");
+        config.save_to_file(&path).unwrap();
+        Config::load_from_file(&path).unwrap()
+    }
+
+    fn ids(config: &Config) -> Vec<&str> {
+        config.schedules.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    fn schedule<'a>(config: &'a Config, id: &str) -> &'a Schedule {
+        config.schedules.iter().find(|s| s.id == id).unwrap()
+    }
+
+    #[test]
+    fn include_glob_is_expanded_in_path_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let b = write_pack(dir.path(), "schedules.d/b.yaml", &["b1", "b2"]);
+        let a = write_pack(dir.path(), "schedules.d/a.yaml", &["a1"]);
+        std::fs::write(dir.path().join("schedules.d/notes.txt"), "not a pack").unwrap();
+
+        let config = load_with_includes(dir.path(), &["schedules.d/*.yaml"], &["own"]);
+        assert_eq!(ids(&config), ["a1", "b1", "b2", "own"]);
+        assert_eq!(schedule(&config, "a1").source.as_deref(), Some(a.as_path()));
+        assert_eq!(schedule(&config, "b2").source.as_deref(), Some(b.as_path()));
+        assert_eq!(schedule(&config, "own").source, config.source_path);
+    }
+
+    #[test]
+    fn include_glob_without_matches_is_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_with_includes(dir.path(), &["schedules.d/*.yaml"], &["own"]);
+        assert_eq!(ids(&config), ["own"]);
+    }
+
+    #[test]
+    fn missing_include_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.include = vec!["missing.yaml".to_string()];
+        let path = dir.path().join("config.yaml");
+        config.save_to_file(&path).unwrap();
+
+        let error = format!("{:#}", Config::load_from_file(&path).unwrap_err());
+        assert!(error.contains("Included file not found"), "{}", error);
+    }
+
+    #[test]
+    fn later_includes_and_main_file_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_pack(dir.path(), "first.yaml", &["shared", "overridden", "first_only"]);
+        let second = write_pack(dir.path(), "second.yaml", &["shared"]);
+
+        let config = load_with_includes(dir.path(), &["first.yaml", "second.yaml"], &["overridden"]);
+        // 置き換えられたスケジュールは最初に読み込まれた位置に残る
+        assert_eq!(ids(&config), ["shared", "overridden", "first_only"]);
+        assert_eq!(schedule(&config, "shared").file, "shared-second.wav");
+        assert_eq!(schedule(&config, "overridden").file, "overridden-main.wav");
+        assert_eq!(schedule(&config, "first_only").source.as_deref(), Some(first.as_path()));
+
+        let overrides: Vec<_> = config.overrides.iter().map(|o| (o.id.as_str(), o.replaced.clone(), o.by.clone())).collect();
+        assert_eq!(
+            overrides,
+            [
+                ("shared", Some(first.clone()), Some(second.clone())),
+                ("overridden", Some(first.clone()), config.source_path.clone()),
+            ]
+        );
+
+        // 別のファイル間での置き換えはエラーにしない
+        let report = config.validate();
+        assert!(!report.issues.iter().any(|i| i.message.contains("duplicate")), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn duplicate_id_in_one_include_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let pack = write_pack(dir.path(), "team.yaml", &["dup", "dup"]);
+
+        let report = load_with_includes(dir.path(), &["team.yaml"], &[]).validate();
+        let issue = report.issues.iter().find(|i| i.message.contains("duplicate")).expect("duplicate id is not reported");
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.schedule_id.as_deref(), Some("dup"));
+        assert_eq!(issue.source.as_deref(), Some(pack.as_path()));
+    }
+
+    #[test]
+    fn validation_reports_the_file_of_each_schedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let pack = dir.path().join("broken.yaml");
+        std::fs::write(
+            &pack,
+            "schedules:\n  - id: broken\n    type: cron\n    cron: \"not a cron\"\n    delivery: none\n    enabled: true\n",
+        )
+        .unwrap();
+
+        let config = load_with_includes(dir.path(), &["broken.yaml"], &["own"]);
+        let report = config.validate();
+        let sources: Vec<_> = report.schedules.iter().map(|s| (s.id.as_str(), s.source.clone())).collect();
+        assert_eq!(sources, [("broken", Some(pack.clone())), ("own", config.source_path.clone())]);
+
+        let issue = report.issues.iter().find(|i| i.message.contains("invalid cron expression")).unwrap();
+        assert_eq!(issue.schedule_id.as_deref(), Some("broken"));
+        assert_eq!(issue.source.as_deref(), Some(pack.as_path()));
+    }
+
+    /// 指定したディレクトリの設定ファイルから読み込んだ扱いの設定
+    fn config_in(dir: &Path, sounds_dir: Option<&str>) -> Config {
+        let mut config = Config::default();
+        config.source_path = Some(dir.join("config.yaml"));
+        config.audio.sounds_dir = sounds_dir.map(str::to_string);
+        config
+    }
+
+    fn touch(path: &Path) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn sound_path_is_resolved_against_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = dir.path().join("second");
+        let chime = touch(&first.join("audios/chime.wav"));
+
+        assert_eq!(config_in(&first, None).resolve_sound_path("audios/chime.wav"), chime);
+        // 設定ファイルの場所が変われば基準も変わる（見つからない場合も設定ファイルのディレクトリ基準）
+        assert_eq!(config_in(&second, None).resolve_sound_path("audios/chime.wav"), second.join("audios/chime.wav"));
+        // 絶対パスはそのまま
+        assert_eq!(config_in(&second, None).resolve_sound_path(&chime.to_string_lossy()), chime);
+    }
+
+    #[test]
+    fn sound_path_falls_back_to_sounds_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+        let library = dir.path().join("library");
+        let bell = touch(&library.join("bell.wav"));
+        let local_chime = touch(&config_dir.join("chime.wav"));
+        touch(&library.join("chime.wav"));
+
+        // 相対パスの sounds_dir は設定ファイルのディレクトリ基準
+        for sounds_dir in ["../library".to_string(), library.to_string_lossy().to_string()] {
+            let config = config_in(&config_dir, Some(&sounds_dir));
+            assert_eq!(config.resolve_sound_path("bell.wav").canonicalize().unwrap(), bell.canonicalize().unwrap());
+            // 設定ファイルのディレクトリにあるものを優先する
+            assert_eq!(config.resolve_sound_path("chime.wav"), local_chime);
+            // どちらにもない場合は設定ファイルのディレクトリ基準
+            assert_eq!(config.resolve_sound_path("gong.wav"), config_dir.join("gong.wav"));
+        }
+    }
+
+    #[test]
+    fn sound_path_expands_home_and_environment_variables() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config_in(dir.path(), None);
+        let home = std::env::home_dir().unwrap();
+        assert_eq!(config.resolve_sound_path("~/chimes/bell.wav"), home.join("chimes/bell.wav"));
+
+        // 環境変数は cargo が設定する CARGO_MANIFEST_DIR を使う（テスト中に環境変数を変更しない）
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(config.resolve_sound_path("$CARGO_MANIFEST_DIR/audios/bell.wav"), manifest_dir.join("audios/bell.wav"));
+        let config = config_in(dir.path(), Some("${CARGO_MANIFEST_DIR}/locales"));
+        assert_eq!(config.resolve_sound_path("en.yaml"), manifest_dir.join("locales/en.yaml"));
+    }
+}
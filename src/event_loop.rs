@@ -0,0 +1,362 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::path::PathBuf;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Duration;
+use tracing::{error, info};
+
+use crate::ipc::{ControlCommand, ControlRequest, ControlResponse};
+use crate::scheduler::{NextFire, ScheduleEvent};
+use crate::systemd;
+use crate::tray::TrayMenuEvent;
+
+/// メインループから呼び出すイベントの処理（アプリケーション本体は `App`）
+pub trait EventHandler {
+    /// トレイメニューの操作（終了が選ばれた場合は true）
+    async fn on_tray_event(&mut self, event: TrayMenuEvent) -> Result<bool>;
+    /// スケジュールイベント
+    fn on_schedule_event(&mut self, event: ScheduleEvent);
+    /// ctl・HTTP・MQTT からの操作
+    fn on_control(&mut self, command: ControlCommand) -> ControlResponse;
+    /// 音声ファイルの変更・削除
+    fn on_sound_changes(&mut self, files: Vec<PathBuf>);
+    /// 設定の再読み込み
+    fn on_reload(&mut self) -> Result<()>;
+    /// トレイの表示を更新
+    fn refresh_status(&mut self);
+    /// 次にトレイの表示が変わるまでの時間（トレイがない場合は None）
+    fn next_status_refresh(&self) -> Option<Duration>;
+}
+
+/// メインループが待ち受けるイベントの受信側
+///
+/// None の受信側と閉じたチャンネルは待ち受けない
+#[derive(Default)]
+pub struct EventSources {
+    pub tray: Option<mpsc::UnboundedReceiver<TrayMenuEvent>>,
+    pub schedule: Option<mpsc::UnboundedReceiver<ScheduleEvent>>,
+    pub control: Option<mpsc::UnboundedReceiver<ControlRequest>>,
+    pub reload: Option<mpsc::UnboundedReceiver<()>>,
+    pub sound_changes: Option<mpsc::UnboundedReceiver<Vec<PathBuf>>>,
+    pub shutdown: Option<mpsc::UnboundedReceiver<()>>,
+    /// 次回実行予定の変更（トレイの表示を更新する）
+    pub next_fire: Option<watch::Receiver<Option<NextFire>>>,
+    /// 一時停止・自動再開（トレイの表示を更新する）
+    pub paused_until: Option<watch::Receiver<Option<DateTime<Local>>>>,
+}
+
+/// チャンネルからイベントを受信（受信側がない・閉じた場合は待ち続ける）
+async fn recv_or_pending<T>(receiver: &mut Option<mpsc::UnboundedReceiver<T>>) -> T {
+    if let Some(rx) = receiver.as_mut()
+        && let Some(value) = rx.recv().await
+    {
+        return value;
+    }
+    *receiver = None;
+    std::future::pending().await
+}
+
+/// 値の変更を待つ（受信側がない・送信側が破棄された場合は待ち続ける）
+async fn changed_or_pending<T>(receiver: &mut Option<watch::Receiver<T>>) {
+    if let Some(rx) = receiver.as_mut()
+        && rx.changed().await.is_ok()
+    {
+        return;
+    }
+    *receiver = None;
+    std::future::pending().await
+}
+
+/// メインイベントループ（終了要求を受けるまでイベントを待ち受けて処理）
+pub async fn run_event_loop<H: EventHandler>(handler: &mut H, sources: &mut EventSources) {
+    // Windows ではトレイアイコンのウィンドウメッセージをこのスレッドで処理する必要がある
+    #[cfg(target_os = "windows")]
+    let mut message_pump = tokio::time::interval(Duration::from_millis(50));
+    #[cfg(target_os = "windows")]
+    message_pump.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        #[cfg(target_os = "windows")]
+        let pump = message_pump.tick();
+        #[cfg(not(target_os = "windows"))]
+        let pump = std::future::pending::<tokio::time::Instant>();
+
+        // ツールチップのカウントダウンは表示が変わるときだけ更新（トレイ表示中のみ）
+        let refresh_delay = handler.next_status_refresh();
+
+        tokio::select! {
+            event = recv_or_pending(&mut sources.tray) => {
+                info!("Received tray menu event: {:?}", event);
+                match handler.on_tray_event(event).await {
+                    Ok(true) => {
+                        info!("Exit requested from tray menu");
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(e) => error!("Error handling tray event: {}", e),
+                }
+            }
+
+            // スケジュールイベントを記録し、次回実行時刻の表示を更新
+            event = recv_or_pending(&mut sources.schedule) => {
+                handler.on_schedule_event(event);
+            }
+
+            // ctl サブコマンドからの操作
+            request = recv_or_pending(&mut sources.control) => {
+                let quit = matches!(request.command, ControlCommand::Quit);
+                let response = handler.on_control(request.command);
+                let _ = request.reply.send(response);
+                if quit {
+                    info!("Exit requested via control command");
+                    break;
+                }
+            }
+
+            // 音声ファイルの変更・削除
+            files = recv_or_pending(&mut sources.sound_changes) => {
+                handler.on_sound_changes(files);
+            }
+
+            // エディタ終了後・SIGHUP・ctl による設定の再読み込み
+            () = recv_or_pending(&mut sources.reload) => {
+                systemd::notify_reloading();
+                if let Err(e) = handler.on_reload() {
+                    error!("{:#}", e);
+                }
+                systemd::notify_ready("Running");
+            }
+
+            () = recv_or_pending(&mut sources.shutdown) => {
+                info!("Received shutdown signal");
+                break;
+            }
+
+            () = changed_or_pending(&mut sources.next_fire) => {
+                handler.refresh_status();
+            }
+
+            () = changed_or_pending(&mut sources.paused_until) => {
+                handler.refresh_status();
+            }
+
+            _ = tokio::time::sleep(refresh_delay.unwrap_or_default()), if refresh_delay.is_some() => {
+                handler.refresh_status();
+            }
+
+            _ = pump => {
+                #[cfg(target_os = "windows")]
+                crate::windows_utils::pump_messages_non_blocking();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+    use crate::scheduler::ScheduleOutcome;
+
+    /// 呼び出された処理を記録する
+    #[derive(Default)]
+    struct FakeHandler {
+        calls: Vec<String>,
+        refresh_delay: Option<Duration>,
+    }
+
+    impl EventHandler for FakeHandler {
+        async fn on_tray_event(&mut self, event: TrayMenuEvent) -> Result<bool> {
+            self.calls.push(format!("tray {:?}", event));
+            Ok(matches!(event, TrayMenuEvent::Exit))
+        }
+
+        fn on_schedule_event(&mut self, event: ScheduleEvent) {
+            self.calls.push(format!("schedule {} {}", event.schedule_id, event.outcome.kind()));
+        }
+
+        fn on_control(&mut self, command: ControlCommand) -> ControlResponse {
+            self.calls.push(format!("control {:?}", command));
+            ControlResponse::ok("ok")
+        }
+
+        fn on_sound_changes(&mut self, files: Vec<PathBuf>) {
+            self.calls.push(format!("sound_changes {}", files.len()));
+        }
+
+        fn on_reload(&mut self) -> Result<()> {
+            self.calls.push("reload".to_string());
+            Ok(())
+        }
+
+        fn refresh_status(&mut self) {
+            self.calls.push("refresh".to_string());
+        }
+
+        fn next_status_refresh(&self) -> Option<Duration> {
+            self.refresh_delay
+        }
+    }
+
+    fn control(command: ControlCommand) -> (ControlRequest, oneshot::Receiver<ControlResponse>) {
+        let (reply, reply_rx) = oneshot::channel();
+        (ControlRequest { command, reply }, reply_rx)
+    }
+
+    /// ループが終了するまで実行（終了しない場合は失敗）
+    async fn run(handler: &mut FakeHandler, sources: &mut EventSources) {
+        tokio::time::timeout(Duration::from_secs(5), run_event_loop(handler, sources))
+            .await
+            .expect("event loop did not exit");
+    }
+
+    #[tokio::test]
+    async fn exits_on_quit_command_after_replying() {
+        let (control_tx, control_rx) = mpsc::unbounded_channel();
+        let mut sources = EventSources {
+            control: Some(control_rx),
+            ..Default::default()
+        };
+        let (status, status_reply) = control(ControlCommand::Status);
+        let (quit, quit_reply) = control(ControlCommand::Quit);
+        let (after_quit, _) = control(ControlCommand::Mute);
+        control_tx.send(status).unwrap();
+        control_tx.send(quit).unwrap();
+        control_tx.send(after_quit).unwrap();
+
+        let mut handler = FakeHandler::default();
+        run(&mut handler, &mut sources).await;
+
+        assert_eq!(handler.calls, ["control Status", "control Quit"]);
+        assert!(status_reply.await.unwrap().ok);
+        assert!(quit_reply.await.unwrap().ok);
+    }
+
+    #[tokio::test]
+    async fn exits_on_shutdown_signal() {
+        let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
+        let (schedule_tx, schedule_rx) = mpsc::unbounded_channel();
+        let mut sources = EventSources {
+            schedule: Some(schedule_rx),
+            shutdown: Some(shutdown_rx),
+            ..Default::default()
+        };
+        schedule_tx
+            .send(ScheduleEvent {
+                schedule_id: "hourly_chime".to_string(),
+                planned_at: Local::now(),
+                triggered_at: Local::now(),
+                outcome: ScheduleOutcome::Fired,
+            })
+            .unwrap();
+
+        let mut handler = FakeHandler::default();
+        let loop_task = run(&mut handler, &mut sources);
+        let signal = async {
+            tokio::task::yield_now().await;
+            shutdown_tx.send(()).unwrap();
+        };
+        tokio::join!(loop_task, signal);
+
+        assert_eq!(handler.calls.last().map(String::as_str), Some("schedule hourly_chime fired"));
+    }
+
+    #[tokio::test]
+    async fn dispatches_each_source_until_tray_exit() {
+        let (tray_tx, tray_rx) = mpsc::unbounded_channel();
+        let (reload_tx, reload_rx) = mpsc::unbounded_channel();
+        let (sound_tx, sound_rx) = mpsc::unbounded_channel();
+        let mut sources = EventSources {
+            tray: Some(tray_rx),
+            reload: Some(reload_rx),
+            sound_changes: Some(sound_rx),
+            ..Default::default()
+        };
+        reload_tx.send(()).unwrap();
+        sound_tx.send(vec![PathBuf::from("chime.wav")]).unwrap();
+
+        let mut handler = FakeHandler::default();
+        let loop_task = run(&mut handler, &mut sources);
+        let exit = async {
+            // 先に送ったイベントが処理されてから終了を選ぶ
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            tray_tx.send(TrayMenuEvent::Exit).unwrap();
+        };
+        tokio::join!(loop_task, exit);
+
+        assert!(handler.calls.contains(&"reload".to_string()));
+        assert!(handler.calls.contains(&"sound_changes 1".to_string()));
+        assert_eq!(handler.calls.last().map(String::as_str), Some("tray Exit"));
+    }
+
+    #[tokio::test]
+    async fn closed_sources_do_not_end_the_loop() {
+        let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
+        let (control_tx, control_rx) = mpsc::unbounded_channel::<ControlRequest>();
+        drop(control_tx);
+        let mut sources = EventSources {
+            control: Some(control_rx),
+            shutdown: Some(shutdown_rx),
+            ..Default::default()
+        };
+
+        let mut handler = FakeHandler::default();
+        let loop_task = run(&mut handler, &mut sources);
+        let signal = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            shutdown_tx.send(()).unwrap();
+        };
+        tokio::join!(loop_task, signal);
+
+        assert!(handler.calls.is_empty());
+        assert!(sources.control.is_none());
+    }
+
+    #[tokio::test]
+    async fn refreshes_status_on_next_fire_change() {
+        let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
+        let (next_fire_tx, next_fire_rx) = watch::channel(None);
+        let mut sources = EventSources {
+            shutdown: Some(shutdown_rx),
+            next_fire: Some(next_fire_rx),
+            ..Default::default()
+        };
+
+        // トレイがない場合は定期的な更新を行わない
+        let mut handler = FakeHandler::default();
+        let loop_task = run(&mut handler, &mut sources);
+        let changes = async {
+            next_fire_tx.send_replace(Some(NextFire {
+                schedule_id: "hourly_chime".to_string(),
+                at: Local::now(),
+            }));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            shutdown_tx.send(()).unwrap();
+        };
+        tokio::join!(loop_task, changes);
+
+        assert_eq!(handler.calls, ["refresh"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refreshes_status_when_countdown_changes() {
+        let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel();
+        let mut sources = EventSources {
+            shutdown: Some(shutdown_rx),
+            ..Default::default()
+        };
+
+        let mut handler = FakeHandler {
+            refresh_delay: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let loop_task = run_event_loop(&mut handler, &mut sources);
+        let signal = async {
+            tokio::time::sleep(Duration::from_secs(150)).await;
+            shutdown_tx.send(()).unwrap();
+        };
+        tokio::join!(loop_task, signal);
+
+        assert_eq!(handler.calls, ["refresh", "refresh"]);
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::scheduler::ScheduleEvent;
+
+/// スケジュール実行履歴（JSON Lines形式で追記）
+pub struct EventHistory {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl EventHistory {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create history directory: {:?}", parent))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history file: {:?}", path))?;
+
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    /// イベントを1行追記
+    pub fn record(&mut self, event: &ScheduleEvent) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, event)
+            .context("Failed to serialize schedule event")?;
+        self.writer.write_all(b"\n")?;
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+            .with_context(|| format!("Failed to flush history file: {:?}", self.path))
+    }
+}
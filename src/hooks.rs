@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use crate::config::{Hook, Schedule};
+
+/// フックの種類（環境変数 `TASKTRAY_CHIME_HOOK` の値）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Fire,
+    Success,
+    Failure,
+}
+
+impl HookKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookKind::Fire => "fire",
+            HookKind::Success => "success",
+            HookKind::Failure => "failure",
+        }
+    }
+}
+
+/// フックに渡すスケジュールの情報
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub schedule_id: String,
+    pub fired_at: DateTime<Local>,
+    pub sound_file: String,
+}
+
+impl HookContext {
+    pub fn new(schedule: &Schedule, fired_at: DateTime<Local>) -> Self {
+        Self {
+            schedule_id: schedule.id.clone(),
+            fired_at,
+            sound_file: schedule.sound_path().to_string_lossy().into_owned(),
+        }
+    }
+}
+
+/// フックのコマンドをシェル経由で実行し、出力をログに記録
+///
+/// 終了コードが 0 以外の場合とタイムアウトした場合はエラーを返す（タイムアウト時はプロセスを終了させる）
+pub async fn run(hook: &Hook, kind: HookKind, context: &HookContext, error: Option<&str>) -> Result<()> {
+    let kind_name = kind.as_str();
+    tracing::info!(schedule_id = %context.schedule_id, hook = kind_name, command = %hook.command, "Running hook");
+
+    let output = execute(hook, kind, context, error).await?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        tracing::info!(schedule_id = %context.schedule_id, hook = kind_name, stream = "stdout", "{}", line);
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        tracing::warn!(schedule_id = %context.schedule_id, hook = kind_name, stream = "stderr", "{}", line);
+    }
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("on_{} hook exited with {}", kind_name, output.status));
+    }
+    tracing::info!(schedule_id = %context.schedule_id, hook = kind_name, "Hook completed");
+    Ok(())
+}
+
+/// 環境変数を設定してコマンドを実行し、終了を待って出力を返す
+async fn execute(hook: &Hook, kind: HookKind, context: &HookContext, error: Option<&str>) -> Result<std::process::Output> {
+    let kind_name = kind.as_str();
+    let mut command = shell_command(&hook.command);
+    command
+        .env("TASKTRAY_CHIME_HOOK", kind_name)
+        .env("TASKTRAY_CHIME_SCHEDULE_ID", &context.schedule_id)
+        .env("TASKTRAY_CHIME_FIRED_AT", context.fired_at.to_rfc3339())
+        .env("TASKTRAY_CHIME_SOUND_FILE", &context.sound_file)
+        .env("TASKTRAY_CHIME_ERROR", error.unwrap_or_default())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let child = command
+        .spawn()
+        .with_context(|| format!("Failed to start on_{} hook: {}", kind_name, hook.command))?;
+    tokio::time::timeout(Duration::from_secs(hook.timeout_seconds), child.wait_with_output())
+        .await
+        .map_err(|_| anyhow::anyhow!("on_{} hook timed out after {} seconds", kind_name, hook.timeout_seconds))?
+        .with_context(|| format!("Failed to wait for on_{} hook", kind_name))
+}
+
+/// OS標準のシェルでコマンド文字列を実行する `Command` を作成
+fn shell_command(command_line: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        // コンソールウィンドウを表示しない
+        command.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
+        command
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext {
+        HookContext {
+            schedule_id: "hourly_chime".to_string(),
+            fired_at: Local::now(),
+            sound_file: "/sounds/chime.wav".to_string(),
+        }
+    }
+
+    fn hook(command: &str, timeout_seconds: u64) -> Hook {
+        Hook { command: command.to_string(), timeout_seconds }
+    }
+
+    #[tokio::test]
+    async fn passes_schedule_through_environment() {
+        let context = context();
+        let output = execute(
+            &hook("echo $TASKTRAY_CHIME_SCHEDULE_ID; echo $TASKTRAY_CHIME_HOOK $TASKTRAY_CHIME_SOUND_FILE; echo $TASKTRAY_CHIME_FIRED_AT; echo \"$TASKTRAY_CHIME_ERROR\" >&2", 5),
+            HookKind::Failure,
+            &context,
+            Some("Audio file not found"),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let expected = format!("hourly_chime\nfailure /sounds/chime.wav\n{}\n", context.fired_at.to_rfc3339());
+        assert_eq!(stdout, expected);
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "Audio file not found\n");
+    }
+
+    #[tokio::test]
+    async fn run_fails_on_nonzero_exit() {
+        let error = run(&hook("exit 3", 5), HookKind::Fire, &context(), None).await.unwrap_err();
+        assert!(error.to_string().starts_with("on_fire hook exited with"), "{}", error);
+        assert!(run(&hook("true", 5), HookKind::Success, &context(), None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn kills_hook_after_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("finished");
+        let command = format!("sleep 2; touch '{}'", marker.display());
+
+        let started = std::time::Instant::now();
+        let error = run(&hook(&command, 1), HookKind::Fire, &context(), None).await.unwrap_err();
+        assert_eq!(error.to_string(), "on_fire hook timed out after 1 seconds");
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // 強制終了されたため最後まで実行されない
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use crate::config::HttpConfig;
+use crate::ipc::{self, ControlCommand, ControlRequest, ControlResponse};
+
+#[derive(Clone)]
+struct HttpState {
+    request_tx: mpsc::UnboundedSender<ControlRequest>,
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PlayFileRequest {
+    file: String,
+}
+
+/// 127.0.0.1 で待ち受けるHTTPエンドポイントを開始
+///
+/// 受け付けた操作は `ctl` サブコマンドと同じく `request_tx` に送る。`cancel` がキャンセルされると終了する
+/// ブラウザ上のページからの操作を防ぐため、`Origin` ヘッダー付きのリクエストと `Host` がループバックでないリクエストは拒否する
+///
+/// - `GET /status`: 状態を取得
+/// - `POST /schedules/{id}/trigger`: スケジュールを今すぐ実行
+/// - `POST /play`: `{"file": "..."}` の音声ファイルを再生（任意のファイルを指定できるため `token` の設定が必要）
+/// - `POST /stop`: 再生中の音声を停止
+pub async fn start(config: &HttpConfig, request_tx: mpsc::UnboundedSender<ControlRequest>, cancel: CancellationToken) -> Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, config.port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind HTTP endpoint on {}", addr))?;
+
+    let router = router(HttpState {
+        request_tx,
+        token: config.token.clone(),
+    });
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router)
+            .with_graceful_shutdown(cancel.cancelled_owned())
+            .await
+        {
+            tracing::error!("HTTP endpoint stopped: {}", e);
+        }
+    });
+
+    tracing::info!("Listening for HTTP requests on http://{}", addr);
+    Ok(())
+}
+
+fn router(state: HttpState) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/schedules/{id}/trigger", post(trigger_schedule))
+        .route("/play", post(play_file))
+        .route("/stop", post(stop))
+        .with_state(state)
+}
+
+async fn status(State(state): State<HttpState>, headers: HeaderMap) -> (StatusCode, Json<ControlResponse>) {
+    handle(&state, &headers, ControlCommand::Status).await
+}
+
+async fn trigger_schedule(
+    State(state): State<HttpState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ControlResponse>) {
+    handle(&state, &headers, ControlCommand::Play { id }).await
+}
+
+async fn play_file(
+    State(state): State<HttpState>,
+    headers: HeaderMap,
+    Json(request): Json<PlayFileRequest>,
+) -> (StatusCode, Json<ControlResponse>) {
+    let command = ControlCommand::PlayFile { file: request.file };
+    if state.token.is_none() {
+        tracing::warn!("Rejected HTTP request without http.token configured: {:?}", command);
+        return (StatusCode::FORBIDDEN, Json(ControlResponse::error("POST /play requires http.token to be set")));
+    }
+    handle(&state, &headers, command).await
+}
+
+async fn stop(State(state): State<HttpState>, headers: HeaderMap) -> (StatusCode, Json<ControlResponse>) {
+    handle(&state, &headers, ControlCommand::Stop).await
+}
+
+/// 送信元とトークンを確認してから操作をメインループに渡す
+async fn handle(state: &HttpState, headers: &HeaderMap, command: ControlCommand) -> (StatusCode, Json<ControlResponse>) {
+    if headers.contains_key(header::ORIGIN) {
+        tracing::warn!("Rejected cross-origin HTTP request: {:?}", command);
+        return (StatusCode::FORBIDDEN, Json(ControlResponse::error("Cross-origin requests are not allowed")));
+    }
+    if !headers.get(header::HOST).and_then(|value| value.to_str().ok()).is_some_and(is_loopback_host) {
+        tracing::warn!("Rejected HTTP request with non-loopback Host header: {:?}", command);
+        return (StatusCode::FORBIDDEN, Json(ControlResponse::error("Invalid Host header")));
+    }
+    if let Some(token) = &state.token {
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|value| value == token);
+        if !authorized {
+            tracing::warn!("Rejected unauthorized HTTP request: {:?}", command);
+            return (StatusCode::UNAUTHORIZED, Json(ControlResponse::error("Unauthorized")));
+        }
+    }
+
+    let response = ipc::dispatch(&state.request_tx, command).await;
+    let status = if response.ok { StatusCode::OK } else { StatusCode::BAD_REQUEST };
+    (status, Json(response))
+}
+
+/// `Host` ヘッダーがループバックアドレスを指しているか（DNS リバインディング対策）
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        // IPv6 アドレス（`[::1]:7878`）
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ルーターを空きポートで起動し、ベースURLとメインループ側の受信チャンネルを返す
+    async fn spawn_router(token: Option<&str>) -> (String, mpsc::UnboundedReceiver<ControlRequest>) {
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = router(HttpState {
+            request_tx,
+            token: token.map(str::to_string),
+        });
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        (format!("http://{}", addr), request_rx)
+    }
+
+    #[tokio::test]
+    async fn rejects_request_without_bearer_token() {
+        let (base, mut request_rx) = spawn_router(Some("secret")).await;
+        let client = reqwest::Client::new();
+
+        let response = client.post(format!("{}/stop", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = client.post(format!("{}/stop", base)).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(request_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn dispatches_trigger_as_play_command() {
+        let (base, mut request_rx) = spawn_router(Some("secret")).await;
+        let app = tokio::spawn(async move {
+            let request = request_rx.recv().await.unwrap();
+            let ControlCommand::Play { id } = request.command else {
+                panic!("unexpected command: {:?}", request.command);
+            };
+            let _ = request.reply.send(ControlResponse::ok(format!("Triggered {}", id)));
+            id
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/schedules/hourly_chime/trigger", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: ControlResponse = response.json().await.unwrap();
+        assert!(body.ok);
+        assert_eq!(body.message, "Triggered hourly_chime");
+        assert_eq!(app.await.unwrap(), "hourly_chime");
+    }
+
+    #[tokio::test]
+    async fn rejects_cross_origin_request() {
+        let (base, mut request_rx) = spawn_router(None).await;
+        let response = reqwest::Client::new()
+            .post(format!("{}/stop", base))
+            .header(header::ORIGIN, "https://example.com")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(request_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn play_requires_token_to_be_configured() {
+        let (base, mut request_rx) = spawn_router(None).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("{}/play", base))
+            .json(&serde_json::json!({ "file": "/etc/passwd" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body: ControlResponse = response.json().await.unwrap();
+        assert_eq!(body.message, "POST /play requires http.token to be set");
+        assert!(request_rx.try_recv().is_err());
+
+        // トークンなしでもスケジュールの実行は受け付ける
+        let app = tokio::spawn(async move {
+            let request = request_rx.recv().await.unwrap();
+            let _ = request.reply.send(ControlResponse::ok("ok"));
+            request.command
+        });
+        let response = client.post(format!("{}/schedules/hourly_chime/trigger", base)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(matches!(app.await.unwrap(), ControlCommand::Play { .. }));
+    }
+
+    #[tokio::test]
+    async fn play_is_dispatched_with_token() {
+        let (base, mut request_rx) = spawn_router(Some("secret")).await;
+        let app = tokio::spawn(async move {
+            let request = request_rx.recv().await.unwrap();
+            let _ = request.reply.send(ControlResponse::ok("Playing"));
+            request.command
+        });
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/play", base))
+            .bearer_auth("secret")
+            .json(&serde_json::json!({ "file": "audios/bell.wav" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let ControlCommand::PlayFile { file } = app.await.unwrap() else {
+            panic!("unexpected command");
+        };
+        assert_eq!(file, "audios/bell.wav");
+    }
+
+    #[test]
+    fn accepts_only_loopback_hosts() {
+        for host in ["127.0.0.1:7878", "localhost:7878", "LOCALHOST", "[::1]:7878", "127.0.0.1"] {
+            assert!(is_loopback_host(host), "{}", host);
+        }
+        for host in ["example.com", "evil.example:7878", "192.168.0.10:7878", "[2001:db8::1]:7878", ""] {
+            assert!(!is_loopback_host(host), "{}", host);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+/// 組み込みのメッセージカタログ（言語コード, YAML）
+const BUILTIN_CATALOGS: [(&str, &str); 2] = [
+    ("ja", include_str!("../locales/ja.yaml")),
+    ("en", include_str!("../locales/en.yaml")),
+];
+
+/// メッセージが見つからない場合に使用する言語
+const FALLBACK_LANGUAGE: &str = "en";
+
+static CATALOG: LazyLock<RwLock<Catalog>> = LazyLock::new(|| RwLock::new(Catalog::load(None, None)));
+
+/// 選択中の言語のメッセージ（キー → 文字列）
+struct Catalog {
+    language: String,
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// 言語を決定してカタログを読み込む
+    ///
+    /// `language` が未指定または `auto` の場合はOSのロケールから判定する
+    fn load(language: Option<&str>, user_dir: Option<&Path>) -> Self {
+        let requested = match language.map(str::trim) {
+            Some(lang) if !lang.is_empty() && !lang.eq_ignore_ascii_case("auto") => normalize_language(lang),
+            _ => detect_language(),
+        };
+
+        let language = if is_available(&requested, user_dir) {
+            requested
+        } else {
+            tracing::warn!("No message catalog for language '{}', using '{}'", requested, FALLBACK_LANGUAGE);
+            FALLBACK_LANGUAGE.to_string()
+        };
+
+        let mut messages = builtin_messages(&language);
+        // ユーザーのカタログで追加・上書き
+        if let Some(dir) = user_dir {
+            let path = dir.join(format!("{}.yaml", language));
+            if path.exists() {
+                match read_catalog(&path) {
+                    Ok(user_messages) => {
+                        tracing::info!("Loaded message catalog: {:?}", path);
+                        messages.extend(user_messages);
+                    }
+                    Err(e) => tracing::warn!("Failed to load message catalog {:?}: {:#}", path, e),
+                }
+            }
+        }
+
+        Self {
+            language,
+            messages,
+            fallback: builtin_messages(FALLBACK_LANGUAGE),
+        }
+    }
+
+    fn get(&self, key: &str) -> String {
+        self.messages
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+/// 表示言語を設定する
+///
+/// `user_dir` に `<言語コード>.yaml` があれば組み込みのカタログに追加・上書きする
+pub fn init(language: Option<&str>, user_dir: Option<&Path>) {
+    let catalog = Catalog::load(language, user_dir);
+    tracing::info!("UI language: {}", catalog.language);
+    *CATALOG.write().unwrap_or_else(|e| e.into_inner()) = catalog;
+}
+
+/// メッセージを取得
+pub fn t(key: &str) -> String {
+    CATALOG.read().unwrap_or_else(|e| e.into_inner()).get(key)
+}
+
+/// メッセージを取得してプレースホルダー `{name}` を置換
+pub fn tf(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(key), |message, (name, value)| {
+        message.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+/// 組み込みまたはユーザーのカタログがある言語か
+pub fn is_available(language: &str, user_dir: Option<&Path>) -> bool {
+    BUILTIN_CATALOGS.iter().any(|(lang, _)| *lang == language)
+        || user_dir.is_some_and(|dir| dir.join(format!("{}.yaml", language)).exists())
+}
+
+/// "ja-JP" や "en_US.UTF-8" を言語コード（"ja", "en"）に変換
+pub fn normalize_language(locale: &str) -> String {
+    locale
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// OSのロケールから言語を判定
+fn detect_language() -> String {
+    match sys_locale::get_locale() {
+        Some(locale) => {
+            tracing::debug!("Detected OS locale: {}", locale);
+            normalize_language(&locale)
+        }
+        None => FALLBACK_LANGUAGE.to_string(),
+    }
+}
+
+fn builtin_messages(language: &str) -> HashMap<String, String> {
+    BUILTIN_CATALOGS
+        .iter()
+        .find(|(lang, _)| *lang == language)
+        .and_then(|(lang, content)| {
+            serde_yaml::from_str(content)
+                .map_err(|e| tracing::error!("Invalid built-in message catalog '{}': {}", lang, e))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn read_catalog(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read message catalog: {:?}", path))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse message catalog: {:?}", path))
+}
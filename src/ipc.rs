@@ -0,0 +1,359 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+/// 実行中のインスタンスへの操作（`ctl` サブコマンド・IPCで共通）
+#[derive(Debug, Clone, Subcommand, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    /// 現在の状態を表示
+    Status,
+    /// 設定ファイルを再読み込み
+    Reload,
+    /// 全スケジュールを一時停止
+    Pause {
+        /// 停止する時間（分）
+        #[arg(long, conflicts_with = "until")]
+        minutes: Option<u32>,
+        /// 再開する時刻（HH:MM）
+        #[arg(long)]
+        until: Option<String>,
+    },
+    /// 一時停止を解除
+    Resume,
+    /// 音声をミュート
+    Mute,
+    /// ミュートを解除
+    Unmute,
+    /// スケジュールを今すぐ実行
+    Play {
+        /// スケジュールID
+        id: String,
+    },
+    /// 音声ファイルを再生
+    PlayFile {
+        /// 音声ファイルのパス
+        file: String,
+    },
+    /// 再生中の音声をすべて停止
+    Stop,
+    /// 全体の音量を変更（実行中のセッションのみ）
+    Volume {
+        /// 音量（0-100）
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        level: u8,
+    },
+    /// スケジュールを有効にして設定ファイルに保存
+    Enable {
+        /// スケジュールID
+        id: String,
+    },
+    /// スケジュールを無効にして設定ファイルに保存
+    Disable {
+        /// スケジュールID
+        id: String,
+    },
+    /// アプリケーションを終了
+    Quit,
+}
+
+/// 操作の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self { ok: true, message: message.into(), data: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, message: message.into(), data: None }
+    }
+}
+
+/// IPCで受け付けた操作（`reply` で結果を返す）
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: oneshot::Sender<ControlResponse>,
+}
+
+/// IPCの接続先（Unix: ソケットファイル、Windows: 名前付きパイプ）
+#[derive(Debug, Clone)]
+pub struct ControlEndpoint {
+    path: PathBuf,
+}
+
+impl ControlEndpoint {
+    /// ユーザーごとの接続先を決定
+    ///
+    /// Unix: `$XDG_RUNTIME_DIR/tasktray-chime.sock`（未設定時はデータディレクトリの `control.sock`）
+    /// Windows: `\\.\pipe\tasktray-chime-<ユーザー名>`
+    pub fn for_user(data_dir: &std::path::Path) -> Self {
+        #[cfg(unix)]
+        {
+            let path = std::env::var_os("XDG_RUNTIME_DIR")
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| PathBuf::from(dir).join("tasktray-chime.sock"))
+                .unwrap_or_else(|| data_dir.join("control.sock"));
+            Self { path }
+        }
+
+        #[cfg(windows)]
+        {
+            let _ = data_dir;
+            let user = std::env::var("USERNAME").unwrap_or_default();
+            Self { path: PathBuf::from(format!(r"\\.\pipe\tasktray-chime-{}", user)) }
+        }
+    }
+
+    /// 接続を受け付けるタスクを開始し、受け付けた操作を `request_tx` に送る
+    ///
+    /// タスクは `cancel` がキャンセルされると終了する
+    pub fn listen(&self, request_tx: mpsc::UnboundedSender<ControlRequest>, cancel: CancellationToken) -> Result<()> {
+        #[cfg(unix)]
+        {
+            let listener = self.bind_unix()?;
+            tokio::spawn(async move {
+                while let Some(accepted) = cancel.run_until_cancelled(listener.accept()).await {
+                    match accepted {
+                        Ok((stream, _)) => {
+                            tokio::spawn(handle_connection(stream, request_tx.clone()));
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to accept control connection: {}", e);
+                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        }
+                    }
+                }
+            });
+        }
+
+        #[cfg(windows)]
+        {
+            use tokio::net::windows::named_pipe::ServerOptions;
+
+            let name = self.path.clone();
+            let mut server = ServerOptions::new()
+                .first_pipe_instance(true)
+                .create(&name)
+                .with_context(|| format!("Failed to create control pipe: {:?}", name))?;
+            tokio::spawn(async move {
+                while let Some(result) = cancel.run_until_cancelled(server.connect()).await {
+                    // 接続に失敗したインスタンスは再利用できないため、どちらの場合も次のインスタンスを作る
+                    let next = match ServerOptions::new().create(&name) {
+                        Ok(server) => server,
+                        Err(e) => {
+                            tracing::error!("Failed to create control pipe, stopping IPC: {}", e);
+                            break;
+                        }
+                    };
+                    let connected = std::mem::replace(&mut server, next);
+                    match result {
+                        Ok(()) => {
+                            tokio::spawn(handle_connection(connected, request_tx.clone()));
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to accept control connection: {}", e);
+                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        }
+                    }
+                }
+            });
+        }
+
+        tracing::info!("Listening for control commands on {:?}", self.path);
+        Ok(())
+    }
+
+    /// 既存のソケットファイルが使われていなければ削除してから待ち受ける
+    #[cfg(unix)]
+    fn bind_unix(&self) -> Result<tokio::net::UnixListener> {
+        if self.path.exists() {
+            if std::os::unix::net::UnixStream::connect(&self.path).is_ok() {
+                return Err(anyhow::anyhow!("Another instance is already listening on {:?}", self.path));
+            }
+            std::fs::remove_file(&self.path)
+                .with_context(|| format!("Failed to remove stale control socket: {:?}", self.path))?;
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create control socket directory: {:?}", parent))?;
+        }
+        let listener = tokio::net::UnixListener::bind(&self.path)
+            .with_context(|| format!("Failed to bind control socket: {:?}", self.path))?;
+
+        // 他のユーザーから操作できないようにする
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&self.path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to set permissions on control socket: {:?}", self.path))?;
+        Ok(listener)
+    }
+
+    /// 待ち受けを終了（Unixではソケットファイルを削除）
+    pub fn cleanup(&self) {
+        #[cfg(unix)]
+        if let Err(e) = std::fs::remove_file(&self.path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove control socket {:?}: {}", self.path, e);
+        }
+    }
+
+    /// 実行中のインスタンスに操作を送信して結果を受け取る
+    pub async fn send(&self, command: &ControlCommand) -> Result<ControlResponse> {
+        #[cfg(unix)]
+        let stream = tokio::net::UnixStream::connect(&self.path)
+            .await
+            .with_context(|| format!("Failed to connect to {:?} (is tasktray-chime running?)", self.path))?;
+
+        #[cfg(windows)]
+        let stream = tokio::net::windows::named_pipe::ClientOptions::new()
+            .open(&self.path)
+            .with_context(|| format!("Failed to connect to {:?} (is tasktray-chime running?)", self.path))?;
+
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut request = serde_json::to_string(command).context("Failed to serialize control command")?;
+        request.push('\n');
+        writer.write_all(request.as_bytes()).await.context("Failed to send control command")?;
+
+        let mut line = String::new();
+        BufReader::new(reader)
+            .read_line(&mut line)
+            .await
+            .context("Failed to read control response")?;
+        serde_json::from_str(&line).context("Invalid control response")
+    }
+}
+
+/// 操作をメインループに渡して結果を待つ
+pub async fn dispatch(request_tx: &mpsc::UnboundedSender<ControlRequest>, command: ControlCommand) -> ControlResponse {
+    tracing::info!("Received control command: {:?}", command);
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if request_tx.send(ControlRequest { command, reply: reply_tx }).is_err() {
+        return ControlResponse::error("Application is shutting down");
+    }
+    reply_rx.await.unwrap_or_else(|_| ControlResponse::error("No response from application"))
+}
+
+/// 1接続につき1行のJSONを受け取り、1行のJSONで応答する
+async fn handle_connection<S>(stream: S, request_tx: mpsc::UnboundedSender<ControlRequest>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(reader).read_line(&mut line).await {
+        tracing::warn!("Failed to read control command: {}", e);
+        return;
+    }
+
+    let response = match serde_json::from_str::<ControlCommand>(&line) {
+        Ok(command) => dispatch(&request_tx, command).await,
+        Err(e) => ControlResponse::error(format!("Invalid control command: {}", e)),
+    };
+
+    let mut body = serde_json::to_string(&response).unwrap_or_default();
+    body.push('\n');
+    if let Err(e) = writer.write_all(body.as_bytes()).await {
+        tracing::warn!("Failed to send control response: {}", e);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// 一時ディレクトリのソケットで待ち受け、受け取った操作を返す
+    fn listen(dir: &std::path::Path) -> (ControlEndpoint, mpsc::UnboundedReceiver<ControlRequest>, CancellationToken) {
+        let endpoint = ControlEndpoint { path: dir.join("control.sock") };
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        endpoint.listen(request_tx, cancel.clone()).unwrap();
+        (endpoint, request_rx, cancel)
+    }
+
+    /// ソケットに1行送って応答の1行を受け取る
+    async fn send_raw(endpoint: &ControlEndpoint, line: &str) -> ControlResponse {
+        let mut stream = tokio::net::UnixStream::connect(&endpoint.path).await.unwrap();
+        stream.write_all(line.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trips_command_and_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let (endpoint, mut requests, cancel) = listen(dir.path());
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                let response = match request.command {
+                    ControlCommand::Volume { level } => ControlResponse {
+                        data: Some(serde_json::json!({ "volume": level })),
+                        ..ControlResponse::ok(format!("Volume set to {}", level))
+                    },
+                    command => ControlResponse::error(format!("Unexpected command: {:?}", command)),
+                };
+                let _ = request.reply.send(response);
+            }
+        });
+
+        let response = endpoint.send(&ControlCommand::Volume { level: 40 }).await.unwrap();
+        assert!(response.ok);
+        assert_eq!(response.message, "Volume set to 40");
+        assert_eq!(response.data, Some(serde_json::json!({ "volume": 40 })));
+
+        let response = endpoint.send(&ControlCommand::Quit).await.unwrap();
+        assert!(!response.ok);
+        assert_eq!(response.message, "Unexpected command: Quit");
+
+        cancel.cancel();
+        endpoint.cleanup();
+        assert!(!endpoint.path.exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let (endpoint, _requests, cancel) = listen(dir.path());
+
+        let response = send_raw(&endpoint, "{\"command\":\"explode\"}\n").await;
+        assert!(!response.ok);
+        assert!(response.message.starts_with("Invalid control command"), "{}", response.message);
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn reports_shutdown_when_application_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let (endpoint, requests, cancel) = listen(dir.path());
+        drop(requests);
+
+        let response = endpoint.send(&ControlCommand::Status).await.unwrap();
+        assert!(!response.ok);
+        assert_eq!(response.message, "Application is shutting down");
+        cancel.cancel();
+    }
+
+    #[tokio::test]
+    async fn refuses_to_replace_a_live_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let (endpoint, _requests, cancel) = listen(dir.path());
+
+        let second = ControlEndpoint { path: endpoint.path.clone() };
+        let (request_tx, _request_rx) = mpsc::unbounded_channel();
+        let error = second.listen(request_tx, CancellationToken::new()).unwrap_err();
+        assert!(error.to_string().contains("Another instance is already listening"), "{}", error);
+        cancel.cancel();
+    }
+}
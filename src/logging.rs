@@ -1,12 +1,17 @@
 use anyhow::Result;
+use std::path::Path;
 use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
-use crate::config::LoggingConfig;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry};
+use crate::config::{LogFormat, LoggingConfig};
 
-pub fn init_logging(config: &LoggingConfig) -> Result<()> {
+/// ログを初期化（`log_dir` は `config.directory` をデータディレクトリ基準で解決したもの）
+///
+/// ファイルへの書き込みは別スレッドで行う。返り値のガードを破棄すると未書き込みのログが出力されるため、終了時まで保持すること
+pub fn init_logging(config: &LoggingConfig, log_dir: &Path) -> Result<WorkerGuard> {
     // ログディレクトリを作成
-    std::fs::create_dir_all(&config.directory)?;
+    std::fs::create_dir_all(log_dir)?;
 
     // ログレベルをパース
     let level = match config.level.to_lowercase().as_str() {
@@ -26,23 +31,31 @@ pub fn init_logging(config: &LoggingConfig) -> Result<()> {
             .filename_prefix("tasktray-chime")
             .filename_suffix("log")
             .max_log_files(config.max_files as usize)
-            .build(&config.directory)?
+            .build(log_dir)?
     } else {
         RollingFileAppender::builder()
             .rotation(Rotation::NEVER)
             .filename_prefix("tasktray-chime")
             .filename_suffix("log")
-            .build(&config.directory)?
+            .build(log_dir)?
     };
 
-    // フォーマッタの設定
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    // フォーマッタの設定（`logging.format` で切り替え）
     let file_layer = tracing_subscriber::fmt::layer()
-        .with_writer(file_appender)
+        .with_writer(file_writer)
         .with_ansi(false)
         .with_target(true)
         .with_thread_ids(false)
         .with_file(true)
         .with_line_number(true);
+    let file_layer: Box<dyn Layer<Registry> + Send + Sync> = match config.format {
+        LogFormat::Text => file_layer.boxed(),
+        LogFormat::Compact => file_layer.compact().with_file(false).with_line_number(false).boxed(),
+        // フィールドはオブジェクトの最上位に出力する（スケジュールIDなどで検索しやすくする）
+        LogFormat::Json => file_layer.json().flatten_event(true).boxed(),
+    };
 
     // コンソール出力レイヤー（開発時用）
     let console_layer = tracing_subscriber::fmt::layer()
@@ -62,9 +75,9 @@ pub fn init_logging(config: &LoggingConfig) -> Result<()> {
         .with(console_layer.with_filter(env_filter))
         .init();
 
-    tracing::info!("Logging initialized with level: {}", config.level);
-    tracing::info!("Log directory: {}", config.directory);
+    tracing::info!("Logging initialized with level: {} (format: {:?})", config.level, config.format);
+    tracing::info!("Log directory: {}", log_dir.display());
     tracing::info!("Log rotation: {}", config.rotate);
 
-    Ok(())
+    Ok(guard)
 }
\ No newline at end of file
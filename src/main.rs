@@ -1,19 +1,48 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app;
+mod autostart;
+mod cli;
 mod config;
+mod event_loop;
+mod history;
+mod hooks;
+mod http;
+mod i18n;
+mod ipc;
 mod logging;
+mod mqtt;
+mod notification;
 mod audio;
+mod paths;
 mod scheduler;
+mod state;
+mod systemd;
 mod tray;
+mod watcher;
+mod webhook;
 
 use anyhow::{Context, Result};
+use clap::Parser;
 use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, error, warn};
 
-use config::Config;
-use audio::AudioPlayer;
-use scheduler::CronScheduler;
-use tray::{SystemTray, TrayMenuEvent};
+use cli::{Cli, Command};
+use app::App;
+use config::{Config, Severity, ValidationIssue};
+use event_loop::EventSources;
+use audio::{AudioPlayer, PlaybackOptions};
+use history::EventHistory;
+use ipc::{ControlCommand, ControlEndpoint, ControlRequest, ControlResponse};
+use mqtt::MqttBridge;
+use paths::{AppPaths, ConfigLocation};
+use scheduler::{CronScheduler, RetryPolicy};
+use state::StateStore;
+use tray::{SystemTray, TrayStatus};
+use watcher::SoundWatcher;
+use webhook::WebhookPublisher;
 
 #[cfg(target_os = "windows")]
 mod windows_utils {
@@ -39,25 +68,52 @@ mod windows_utils {
             processed > 0
         }
     }
+
+    /// 起動元のコンソールに接続する（リリースビルドは GUI サブシステムのため、サブコマンドの出力先がない）
+    ///
+    /// エクスプローラーから起動した場合など、接続できるコンソールがなければ何もしない
+    pub fn attach_parent_console() {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+        unsafe {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 設定ファイルを読み込み（存在しない場合は作成）
-    // 実行ファイルと同じディレクトリにconfig.yamlを配置
-    let config_path = {
-        let exe_path = std::env::current_exe()
-            .context("Failed to get executable path")?;
-        let exe_dir = exe_path.parent()
-            .context("Failed to get executable directory")?;
-        exe_dir.join("config.yaml")
-    };
+    let cli = Cli::parse();
+
+    // 設定ファイルの場所を決定
+    // --config → TASKTRAY_CHIME_CONFIG → 実行ファイルのディレクトリ → 標準ディレクトリ
+    let paths = AppPaths::resolve(cli.config)
+        .context("Failed to resolve application paths")?;
+    let config_path = paths.config_file.clone();
+
+    // サブコマンドの結果をコンソールに表示できるようにする
+    #[cfg(target_os = "windows")]
+    if cli.command.is_some() {
+        windows_utils::attach_parent_console();
+    }
+
+    match cli.command {
+        Some(Command::Validate) => return run_validate(&config_path),
+        Some(Command::Export { output, merged, force }) => {
+            return run_export(&config_path, &output, merged, force);
+        }
+        Some(Command::Ctl { command }) => return run_ctl(&paths, &command).await,
+        None => {}
+    }
     
+    // 設定ファイルを読み込み（存在しない場合は作成）
     let config = Config::load_or_create_default(&config_path)
         .context("Failed to load or create config file")?;
 
     // ログシステムを初期化
-    logging::init_logging(&config.logging)
+    let log_dir = paths.resolve_data_path(&config.logging.directory);
+    // ガードは終了処理の最後まで保持する（破棄時に未書き込みのログを出力）
+    let log_guard = logging::init_logging(&config.logging, &log_dir)
         .context("Failed to initialize logging system")?;
 
     // panicハンドラーを設定してpanicログもファイルに出力
@@ -83,7 +139,12 @@ async fn main() -> Result<()> {
         eprintln!("PANIC: {} at {}:{}", msg, location.file(), location.line());
     }));
 
+    // 表示言語を設定
+    i18n::init(config.ui.language.as_deref(), config.locales_dir().as_deref());
+
     info!("Starting Tasktray Chime application");
+    info!("Config file: {:?} ({:?})", config_path, paths.location);
+    info!("Data directory: {:?}", paths.data_dir);
 
     // 音声プレイヤーを初期化
     let audio_player = Arc::new(
@@ -92,14 +153,7 @@ async fn main() -> Result<()> {
     );
 
     // 音声ファイルを事前にロード
-    for schedule in &config.schedules {
-        if schedule.enabled {
-            info!("Preloading audio file: {}", schedule.file);
-            if let Err(e) = audio_player.preload_sound(&schedule.file) {
-                error!("Failed to preload sound file '{}': {}", schedule.file, e);
-            }
-        }
-    }
+    let preload_errors = App::preload_sounds(&audio_player, &config);
 
     // cronスケジューラーを初期化
     let mut scheduler = CronScheduler::new(audio_player.clone());
@@ -108,150 +162,295 @@ async fn main() -> Result<()> {
             error!("Failed to add schedule: {}", e);
         }
     }
+    scheduler.set_quiet_hours(config.behavior.quiet_hours.clone());
+    scheduler.set_retry_policy(RetryPolicy::from_config(&config.behavior));
 
-    // システムトレイを初期化
-    let mut system_tray = SystemTray::new()
-        .context("Failed to initialize system tray")?;
-
-    // スケジューラーを開始
-    let mut schedule_events = scheduler.start().await
-        .context("Failed to start cron scheduler")?;
+    // 前回終了時のスヌーズ・スキップ・一時停止・ミュートを復元
+    let state = match StateStore::open(paths.state_file()) {
+        Ok(state) => {
+            let saved = state.state();
+            scheduler.restore_pending_fires(saved.pending.clone());
+            scheduler.restore_paused_until(saved.paused_until);
+            if saved.muted {
+                audio_player.set_muted(true);
+            }
+            Some(state)
+        }
+        Err(e) => {
+            warn!("Failed to load state file, saved state will not be restored: {:#}", e);
+            None
+        }
+    };
 
-    // 初期化後にメニューを更新して正確な自動起動状態を表示
-    if let Err(e) = system_tray.update_menu() {
-        warn!("Failed to update tray menu after initialization: {}", e);
-    }
+    // システムトレイを初期化
+    // --config や環境変数で指定した設定ファイルは自動起動時にも引き継ぐ
+    let autostart_args = match paths.location {
+        ConfigLocation::CommandLine | ConfigLocation::Environment => {
+            let absolute = std::path::absolute(&config_path).unwrap_or_else(|_| config_path.clone());
+            vec!["--config".into(), absolute.into_os_string()]
+        }
+        ConfigLocation::Portable | ConfigLocation::Platform => Vec::new(),
+    };
+    let autostart = autostart::platform_autostart(autostart_args);
 
-    // シャットダウンチャンネル
-    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
-    
-    // スケジュールイベント処理用のワーカータスク
-    let shutdown_tx_clone = shutdown_tx;
-    tokio::spawn(async move {
-        loop {
-            tokio::select! {
-                Some(event) = schedule_events.recv() => {
-                    info!("Schedule '{}' executed at {}", 
-                          event.schedule_id, 
-                          event.triggered_at.format("%Y-%m-%d %H:%M:%S"));
+    // トレイを表示できない環境ではヘッドレスモードで動作
+    let headless = cli.headless || !tray::tray_host_available();
+    let system_tray = if headless {
+        info!("Running in headless mode (no tray icon)");
+        None
+    } else {
+        match SystemTray::new(config_path.clone(), autostart, &config.schedules) {
+            Ok(mut system_tray) => {
+                if let Err(e) = system_tray.set_pause_times(config.ui.pause_until_times()) {
+                    warn!("Failed to update tray menu: {}", e);
                 }
-                
-                _ = &mut shutdown_rx => {
-                    info!("Schedule event handler received shutdown signal");
-                    break;
+                if let Err(e) = system_tray.set_sound_info(audio_player.sound_infos()) {
+                    warn!("Failed to update tray menu: {}", e);
                 }
+                Some(system_tray)
+            }
+            Err(e) => {
+                warn!("Failed to initialize system tray, falling back to headless mode: {:#}", e);
+                None
             }
         }
-        info!("Schedule event handler task terminated");
-    });
+    };
 
-    info!("All systems initialized, entering main event loop");
+    // スケジューラーを開始
+    // 終了時にスケジューラー・IPC・シグナル待ちのタスクを止める
+    let shutdown = CancellationToken::new();
+    let schedule_events = scheduler.start(shutdown.clone()).await
+        .context("Failed to start cron scheduler")?;
 
-    // メインイベントループ（トレイイベント処理に専念）
-    loop {
-        // Windows環境: 効率的なメッセージポンプ
-        #[cfg(target_os = "windows")]
-        {
-            windows_utils::pump_messages_non_blocking();
+    // 実行履歴ファイル
+    let history = match EventHistory::open(paths.history_file()) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            warn!("Failed to open history file, history will not be recorded: {}", e);
+            None
         }
+    };
 
-        // トレイメニューイベントを短いタイムアウトで処理
-        if let Some(event) = system_tray.recv_menu_event_with_timeout(50).await {
-            info!("Received tray menu event: {:?}", event);
-            match handle_tray_event(event, &mut system_tray, &config).await {
-                Ok(should_exit) => {
-                    if should_exit {
-                        info!("Exit requested from tray menu");
-                        break;
-                    }
-                }
-                Err(e) => error!("Error handling tray event: {}", e),
-            }
-            continue;
+    // エディタ終了後・SIGHUP による設定再読み込み要求
+    let (reload_tx, reload_rx) = mpsc::unbounded_channel::<()>();
+    // Ctrl+C・SIGTERM による終了要求
+    let (shutdown_tx, shutdown_rx) = mpsc::unbounded_channel::<()>();
+    spawn_signal_handlers(reload_tx.clone(), shutdown_tx, shutdown.clone())?;
+
+    // ctl サブコマンドからの操作を受け付ける
+    let (control_tx, control_rx) = mpsc::unbounded_channel::<ControlRequest>();
+    let control_endpoint = ControlEndpoint::for_user(&paths.data_dir);
+    if let Err(e) = control_endpoint.listen(control_tx.clone(), shutdown.clone()) {
+        warn!("Failed to start control endpoint, ctl commands will not be available: {:#}", e);
+    }
+
+    // HTTPエンドポイント（localhost のみ）も同じ操作として受け付ける
+    if let Some(http_config) = &config.http
+        && let Err(e) = http::start(http_config, control_tx.clone(), shutdown.clone()).await
+    {
+        warn!("Failed to start HTTP endpoint: {:#}", e);
+    }
+
+    // MQTT ブローカーからの操作も同じく受け付ける
+    let mqtt = config.mqtt.as_ref().and_then(|mqtt_config| {
+        MqttBridge::start(mqtt_config, control_tx.clone(), shutdown.clone())
+            .map_err(|e| warn!("Failed to start MQTT client: {:#}", e))
+            .ok()
+    });
+    drop(control_tx);
+
+    let webhooks = match WebhookPublisher::new(&config.webhooks) {
+        Ok(webhooks) => Some(webhooks),
+        Err(e) => {
+            warn!("Webhooks are disabled: {:#}", e);
+            None
         }
-        
-        // Ctrl+C シグナルをチェック
-        if tokio::select! {
-            _ = tokio::signal::ctrl_c() => { true }
-            _ = tokio::time::sleep(tokio::time::Duration::from_millis(10)) => { false }
-        } {
-            info!("Received shutdown signal");
-            break;
+    };
+
+    // 音声ファイルの置き換え・削除を検知して事前ロードし直す
+    let (sound_watcher, sound_changes) = match SoundWatcher::new() {
+        Ok((watcher, changes)) => (Some(watcher), Some(changes)),
+        Err(e) => {
+            warn!("Failed to watch audio files, changes will require a reload: {:#}", e);
+            (None, None)
         }
-        
-        // 短いスリープでCPU使用率を抑制
-        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-    }
+    };
+
+    let mut app = App {
+        config_path,
+        log_dir,
+        config,
+        audio_player,
+        scheduler,
+        history,
+        webhooks,
+        mqtt,
+        state,
+        reload_tx,
+        sound_watcher,
+        tray: system_tray,
+        tray_status: TrayStatus {
+            errors: preload_errors,
+            ..Default::default()
+        },
+    };
+    app.watch_sound_files();
+    app.refresh_tray_status();
+    app.sync_pending_fires();
+
+    let mut sources = EventSources {
+        tray: app.tray.as_mut().and_then(SystemTray::take_menu_events),
+        schedule: Some(schedule_events),
+        control: Some(control_rx),
+        reload: Some(reload_rx),
+        sound_changes,
+        shutdown: Some(shutdown_rx),
+        next_fire: Some(app.scheduler.subscribe_next_fire()),
+        paused_until: Some(app.scheduler.subscribe_paused_until()),
+    };
+
+    info!("All systems initialized, entering main event loop");
+    systemd::notify_ready(if headless { "Running (headless)" } else { "Running" });
+
+    event_loop::run_event_loop(&mut app, &mut sources).await;
 
     // 終了処理
     info!("Shutting down application");
-    
-    // ワーカータスクにシャットダウンシグナルを送信
-    let _ = shutdown_tx_clone.send(());
-    
-    // システムトレイの終了処理
-    system_tray.shutdown();
-    
-    // スケジューラーの終了処理
-    scheduler.stop();
-    
-    // バックグラウンドタスクが終了するまで待機
-    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-    
+    systemd::notify_stopping();
+    shutdown.cancel();
+    control_endpoint.cleanup();
+
+    // 実行中の再生を待ち、履歴と状態を保存
+    app.shutdown(sources.schedule.as_mut()).await;
+
     info!("Application shutdown complete");
-    
-    // 確実にプロセスを終了
+    drop(log_guard);
+
+    // エディタの終了待ちなどのブロッキングタスクを待たずにプロセスを終了
     std::process::exit(0);
 }
 
-/// トレイメニューイベントを処理
-async fn handle_tray_event(
-    event: TrayMenuEvent, 
-    system_tray: &mut SystemTray,
-    config: &Config
-) -> Result<bool> {
-    match event {
-        TrayMenuEvent::ToggleAutoStart => {
-            let current_status = system_tray.get_autostart_status();
-            let new_status = !current_status;
-            
-            match system_tray.set_autostart_status(new_status) {
-                Ok(()) => {
-                    info!("Auto-start {} {}", 
-                          if new_status { "enabled" } else { "disabled" },
-                          if new_status { "✓" } else { "✗" });
-                }
-                Err(e) => {
-                    error!("Failed to toggle auto-start: {}", e);
-                }
+/// シグナルを受けて設定の再読み込み・終了を要求するタスクを開始
+///
+/// Ctrl+C: 終了、Unix のみ SIGHUP: 再読み込み、SIGTERM: 終了
+///
+/// タスクは `cancel` がキャンセルされると終了する
+fn spawn_signal_handlers(
+    reload_tx: mpsc::UnboundedSender<()>,
+    shutdown_tx: mpsc::UnboundedSender<()>,
+    cancel: CancellationToken,
+) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut hangup = signal(SignalKind::hangup()).context("Failed to install SIGHUP handler")?;
+        let mut terminate = signal(SignalKind::terminate()).context("Failed to install SIGTERM handler")?;
+        let hangup_cancel = cancel.clone();
+        tokio::spawn(async move {
+            while let Some(Some(())) = hangup_cancel.run_until_cancelled(hangup.recv()).await {
+                info!("Received SIGHUP, reloading config");
+                let _ = reload_tx.send(());
             }
-            Ok(false)
-        }
+        });
+        let shutdown_tx = shutdown_tx.clone();
+        let terminate_cancel = cancel.clone();
+        tokio::spawn(async move {
+            if let Some(Some(())) = terminate_cancel.run_until_cancelled(terminate.recv()).await {
+                info!("Received SIGTERM");
+                let _ = shutdown_tx.send(());
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    drop(reload_tx);
 
-        TrayMenuEvent::OpenConfig => {
-            match SystemTray::open_config_file() {
-                Ok(()) => info!("Opened config file"),
-                Err(e) => error!("Failed to open config file: {}", e),
+    tokio::spawn(async move {
+        match cancel.run_until_cancelled(tokio::signal::ctrl_c()).await {
+            Some(Ok(())) => {
+                info!("Received Ctrl+C");
+                let _ = shutdown_tx.send(());
             }
-            Ok(false)
+            Some(Err(e)) => warn!("Failed to listen for Ctrl+C: {}", e),
+            None => {}
         }
+    });
+    Ok(())
+}
+
+/// 実行中のインスタンスに操作を送信して結果を標準出力に表示
+async fn run_ctl(paths: &AppPaths, command: &ControlCommand) -> Result<()> {
+    let endpoint = ControlEndpoint::for_user(&paths.data_dir);
+    let ControlResponse { ok, message, data } = endpoint.send(command).await?;
+
+    println!("{}", message);
+    if let Some(data) = data {
+        println!("{}", serde_json::to_string_pretty(&data).context("Failed to format response")?);
+    }
+
+    if !ok {
+        return Err(anyhow::anyhow!("Command failed: {}", message));
+    }
+    Ok(())
+}
+
+/// 設定ファイルを検証して結果を標準出力に表示
+fn run_validate(config_path: &std::path::Path) -> Result<()> {
+    let config = Config::load_from_file(config_path)
+        .context("Failed to load config file")?;
 
-        TrayMenuEvent::OpenLogsDir => {
-            match SystemTray::open_logs_directory(&config.logging.directory) {
-                Ok(()) => info!("Opened logs directory"),
-                Err(e) => error!("Failed to open logs directory: {}", e),
+    let mut report = config.validate();
+
+    // 音声ファイルをデコードして長さなどを確認
+    let mut sounds = Vec::new();
+    for schedule in config.schedules.iter().filter(|s| s.delivery.plays_sound() && s.sound_path().exists()) {
+        let options = PlaybackOptions::for_schedule(schedule);
+        match audio::inspect_sound(schedule.sound_path(), &options, config.audio.stream_threshold_bytes()) {
+            Ok(info) => {
+                if let Some(message) = audio::check_fire_interval(schedule, &info) {
+                    report.issues.push(ValidationIssue {
+                        severity: Severity::Warning,
+                        schedule_id: Some(schedule.id.clone()),
+                        source: schedule.source.clone(),
+                        message,
+                    });
+                }
+                sounds.push((schedule.id.clone(), info));
             }
-            Ok(false)
+            Err(e) => report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                schedule_id: Some(schedule.id.clone()),
+                source: schedule.source.clone(),
+                message: format!("{:#}", e),
+            }),
         }
+    }
 
-        TrayMenuEvent::Exit => {
-            info!("Exit requested from tray menu");
-            
-            // システムトレイの終了処理
-            system_tray.shutdown();
-            
-            // 確実にプロセスを終了
-            std::process::exit(0);
+    println!("{}", report);
+    if !sounds.is_empty() {
+        println!("Sounds:");
+        for (id, info) in &sounds {
+            println!("  {}: {}", id, info);
         }
     }
+
+    if report.error_count() > 0 {
+        return Err(anyhow::anyhow!("Config validation failed with {} error(s)", report.error_count()));
+    }
+    Ok(())
+}
+
+/// 設定ファイルを別形式に変換して書き出す
+fn run_export(config_path: &std::path::Path, output: &std::path::Path, merged: bool, force: bool) -> Result<()> {
+    if output.exists() && !force {
+        return Err(anyhow::anyhow!("Output file already exists: {:?} (use --force to overwrite)", output));
+    }
+
+    let config = Config::load_from_file(config_path)
+        .context("Failed to load config file")?;
+    config.export_to_file(output, merged)
+        .with_context(|| format!("Failed to export config to {:?}", output))?;
+
+    println!("Exported {:?} -> {:?}", config_path, output);
+    Ok(())
 }
@@ -0,0 +1,343 @@
+use anyhow::{Context, Result};
+use rumqttc::{AsyncClient, Event, EventLoop, LastWill, MqttOptions, Packet, QoS};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use crate::config::MqttConfig;
+use crate::ipc::{self, ControlCommand, ControlRequest, ControlResponse};
+use crate::scheduler::ScheduleEvent;
+
+/// 再接続の待ち時間（初回。失敗するたびに2倍にし、上限で止める）
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 終了時に offline の送信を待つ最大時間
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 送信待ちのリクエストの上限（超えた分のイベントは破棄する）
+const REQUEST_CAPACITY: usize = 64;
+
+/// トピック名（`topic_prefix` 基準）
+#[derive(Clone)]
+struct Topics {
+    /// "online" / "offline"（retain、Last Will）
+    status: String,
+    /// スケジュールイベント（`events/<スケジュールID>`）
+    events: String,
+    /// 操作を受け付ける（`ctl` と同じ JSON）
+    command: String,
+    /// 操作の結果
+    command_result: String,
+}
+
+impl Topics {
+    fn new(prefix: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        Self {
+            status: format!("{}/status", prefix),
+            events: format!("{}/events", prefix),
+            command: format!("{}/command", prefix),
+            command_result: format!("{}/command/result", prefix),
+        }
+    }
+}
+
+/// MQTT ブローカーとの接続（イベントの送信と操作の受信）
+pub struct MqttBridge {
+    client: AsyncClient,
+    topics: Topics,
+    task: JoinHandle<()>,
+}
+
+impl MqttBridge {
+    /// 接続を開始（接続できるまでバックグラウンドで再試行する）
+    ///
+    /// 受け付けた操作は `request_tx` に送る。`cancel` がキャンセルされると offline を送信して切断する
+    pub fn start(config: &MqttConfig, request_tx: mpsc::UnboundedSender<ControlRequest>, cancel: CancellationToken) -> Result<Self> {
+        if config.client_id.trim().is_empty() {
+            return Err(anyhow::anyhow!("mqtt.client_id must not be empty"));
+        }
+        let topics = Topics::new(&config.topic_prefix);
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(config.keep_alive_seconds));
+        options.set_last_will(LastWill::new(&topics.status, "offline", QoS::AtLeastOnce, true));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+        let task = tokio::spawn(run(client.clone(), eventloop, topics.clone(), request_tx, cancel));
+
+        tracing::info!("MQTT client connecting to {}:{} (topic prefix: {})", config.host, config.port, config.topic_prefix);
+        Ok(Self { client, topics, task })
+    }
+
+    /// スケジュールイベントを `events/<スケジュールID>` に送信
+    pub fn publish_event(&self, event: &ScheduleEvent) {
+        let topic = format!("{}/{}", self.topics.events, event.schedule_id);
+        let result = serde_json::to_vec(event)
+            .context("Failed to serialize schedule event")
+            .and_then(|payload| {
+                self.client
+                    .try_publish(&topic, QoS::AtLeastOnce, false, payload)
+                    .context("MQTT request queue is full or closed")
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to publish schedule event to MQTT topic {}: {:#}", topic, e);
+        }
+    }
+
+    /// 切断処理の完了を待つ（`cancel` のキャンセル後に呼ぶ）
+    pub async fn wait_closed(self) {
+        if tokio::time::timeout(DISCONNECT_TIMEOUT * 2, self.task).await.is_err() {
+            tracing::warn!("Timed out waiting for MQTT client to disconnect");
+        }
+    }
+}
+
+/// イベントループを回し、接続・受信・再接続・切断を処理
+async fn run(
+    client: AsyncClient,
+    mut eventloop: EventLoop,
+    topics: Topics,
+    request_tx: mpsc::UnboundedSender<ControlRequest>,
+    cancel: CancellationToken,
+) {
+    let mut reconnect_delay = RECONNECT_INITIAL_DELAY;
+    let mut connected = false;
+
+    loop {
+        let event = tokio::select! {
+            _ = cancel.cancelled() => break,
+            event = eventloop.poll() => event,
+        };
+
+        match event {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                tracing::info!("Connected to MQTT broker");
+                connected = true;
+                reconnect_delay = RECONNECT_INITIAL_DELAY;
+                // クリーンセッションのため接続のたびに購読し直す
+                if let Err(e) = client.try_subscribe(&topics.command, QoS::AtLeastOnce) {
+                    tracing::warn!("Failed to subscribe to MQTT topic {}: {}", topics.command, e);
+                }
+                if let Err(e) = client.try_publish(&topics.status, QoS::AtLeastOnce, true, "online") {
+                    tracing::warn!("Failed to publish MQTT status: {}", e);
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == topics.command => {
+                let response = match serde_json::from_slice::<ControlCommand>(&publish.payload) {
+                    Ok(command) if !is_remote_command(&command) => {
+                        tracing::warn!("Rejected control command not allowed over MQTT: {:?}", command);
+                        ControlResponse::error("Command is not allowed over MQTT")
+                    }
+                    Ok(command) => {
+                        // 応答を待つ間もイベントループを止めない
+                        let client = client.clone();
+                        let request_tx = request_tx.clone();
+                        let result_topic = topics.command_result.clone();
+                        tokio::spawn(async move {
+                            let response = ipc::dispatch(&request_tx, command).await;
+                            publish_response(&client, &result_topic, &response);
+                        });
+                        continue;
+                    }
+                    Err(e) => ControlResponse::error(format!("Invalid control command: {}", e)),
+                };
+                publish_response(&client, &topics.command_result, &response);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if connected {
+                    tracing::warn!("Lost connection to MQTT broker: {}", e);
+                    connected = false;
+                }
+                tracing::debug!("MQTT connection failed, retrying in {:?}: {}", reconnect_delay, e);
+                // 待機中も終了要求に応じる
+                if cancel.run_until_cancelled(tokio::time::sleep(reconnect_delay)).await.is_none() {
+                    break;
+                }
+                reconnect_delay = (reconnect_delay * 2).min(RECONNECT_MAX_DELAY);
+            }
+        }
+    }
+
+    if connected {
+        disconnect(&client, &mut eventloop, &topics).await;
+    }
+    tracing::info!("MQTT client stopped");
+}
+
+/// MQTT から受け付ける操作か（スケジュールの実行・ミュート・音量・スケジュールの有効/無効のみ）
+///
+/// ブローカーに接続できる他のクライアントから終了・設定の再読み込み・任意のファイルの再生をさせない
+fn is_remote_command(command: &ControlCommand) -> bool {
+    matches!(
+        command,
+        ControlCommand::Play { .. }
+            | ControlCommand::Mute
+            | ControlCommand::Unmute
+            | ControlCommand::Volume { .. }
+            | ControlCommand::Enable { .. }
+            | ControlCommand::Disable { .. }
+    )
+}
+
+/// offline を送信してから切断（送信されるまでイベントループを回す）
+async fn disconnect(client: &AsyncClient, eventloop: &mut EventLoop, topics: &Topics) {
+    if let Err(e) = client.try_publish(&topics.status, QoS::AtLeastOnce, true, "offline") {
+        tracing::warn!("Failed to publish MQTT status: {}", e);
+    }
+    if let Err(e) = client.try_disconnect() {
+        tracing::warn!("Failed to disconnect from MQTT broker: {}", e);
+        return;
+    }
+
+    let drain = async {
+        loop {
+            match eventloop.poll().await {
+                Ok(Event::Outgoing(rumqttc::Outgoing::Disconnect)) | Err(_) => break,
+                Ok(_) => {}
+            }
+        }
+    };
+    if tokio::time::timeout(DISCONNECT_TIMEOUT, drain).await.is_err() {
+        tracing::warn!("Timed out disconnecting from MQTT broker");
+    }
+}
+
+fn publish_response(client: &AsyncClient, topic: &str, response: &ControlResponse) {
+    let payload = serde_json::to_vec(response).unwrap_or_default();
+    if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, false, payload) {
+        tracing::warn!("Failed to publish MQTT command result: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rumqttc::Publish;
+
+    /// ローカルのブローカー（`MQTT_TEST_HOST`、既定 localhost:1883）
+    fn test_config(name: &str) -> MqttConfig {
+        let host = std::env::var("MQTT_TEST_HOST").unwrap_or_else(|_| "localhost".to_string());
+        MqttConfig {
+            host,
+            port: 1883,
+            client_id: format!("tasktray-chime-test-{}-{}", name, std::process::id()),
+            username: None,
+            password: None,
+            // 前回の実行で残った retain のメッセージを受け取らないよう実行ごとに分ける
+            topic_prefix: format!("tasktray-chime-test/{}/{}", std::process::id(), name),
+            keep_alive_seconds: 5,
+        }
+    }
+
+    /// `<prefix>/#` を購読する確認用のクライアント
+    async fn observe(config: &MqttConfig) -> (AsyncClient, mpsc::UnboundedReceiver<Publish>) {
+        let options = MqttOptions::new(format!("{}-observer", config.client_id), &config.host, config.port);
+        let (client, mut eventloop) = AsyncClient::new(options, REQUEST_CAPACITY);
+        client.subscribe(format!("{}/#", config.topic_prefix), QoS::AtLeastOnce).await.unwrap();
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        if tx.send(publish).is_err() {
+                            break;
+                        }
+                    }
+                    // 購読が完了するまで待つ
+                    Ok(_) => {}
+                    Err(e) => panic!("observer connection failed: {}", e),
+                }
+            }
+        });
+        (client, rx)
+    }
+
+    /// 指定したトピックのメッセージを待つ
+    async fn expect(rx: &mut mpsc::UnboundedReceiver<Publish>, topic: &str) -> String {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let publish = rx.recv().await.expect("observer stopped");
+                if publish.topic == topic {
+                    return String::from_utf8_lossy(&publish.payload).into_owned();
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("no message on {}", topic))
+    }
+
+    /// メインループの代わりに操作を受け取って応答する
+    fn fake_app() -> (mpsc::UnboundedSender<ControlRequest>, mpsc::UnboundedReceiver<ControlCommand>) {
+        let (request_tx, mut request_rx) = mpsc::unbounded_channel::<ControlRequest>();
+        let (handled_tx, handled_rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(request) = request_rx.recv().await {
+                let _ = request.reply.send(ControlResponse::ok("ok"));
+                let _ = handled_tx.send(request.command);
+            }
+        });
+        (request_tx, handled_rx)
+    }
+
+    #[test]
+    fn accepts_only_remote_commands() {
+        assert!(is_remote_command(&ControlCommand::Play { id: "hourly_chime".to_string() }));
+        assert!(is_remote_command(&ControlCommand::Mute));
+        assert!(is_remote_command(&ControlCommand::Volume { level: 40 }));
+        assert!(is_remote_command(&ControlCommand::Disable { id: "hourly_chime".to_string() }));
+        assert!(!is_remote_command(&ControlCommand::Quit));
+        assert!(!is_remote_command(&ControlCommand::Reload));
+        assert!(!is_remote_command(&ControlCommand::PlayFile { file: "/etc/passwd".to_string() }));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local MQTT broker (e.g. mosquitto) on localhost:1883"]
+    async fn publishes_status_and_handles_commands() {
+        let config = test_config("commands");
+        let topics = Topics::new(&config.topic_prefix);
+        let (observer, mut messages) = observe(&config).await;
+        let (request_tx, mut handled) = fake_app();
+        let cancel = CancellationToken::new();
+
+        let bridge = MqttBridge::start(&config, request_tx, cancel.clone()).unwrap();
+        assert_eq!(expect(&mut messages, &topics.status).await, "online");
+
+        observer.publish(&topics.command, QoS::AtLeastOnce, false, r#"{"command": "mute"}"#).await.unwrap();
+        let response: ControlResponse = serde_json::from_str(&expect(&mut messages, &topics.command_result).await).unwrap();
+        assert!(response.ok);
+        assert!(matches!(handled.recv().await, Some(ControlCommand::Mute)));
+
+        observer.publish(&topics.command, QoS::AtLeastOnce, false, r#"{"command": "quit"}"#).await.unwrap();
+        let response: ControlResponse = serde_json::from_str(&expect(&mut messages, &topics.command_result).await).unwrap();
+        assert!(!response.ok);
+        assert!(handled.try_recv().is_err());
+
+        cancel.cancel();
+        bridge.wait_closed().await;
+        assert_eq!(expect(&mut messages, &topics.status).await, "offline");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local MQTT broker (e.g. mosquitto) on localhost:1883"]
+    async fn last_will_reports_offline_on_lost_connection() {
+        let config = test_config("last_will");
+        let topics = Topics::new(&config.topic_prefix);
+        let (_observer, mut messages) = observe(&config).await;
+        let (request_tx, _handled) = fake_app();
+
+        let bridge = MqttBridge::start(&config, request_tx, CancellationToken::new()).unwrap();
+        assert_eq!(expect(&mut messages, &topics.status).await, "online");
+
+        // DISCONNECT を送らずに接続を切る（異常終了）
+        bridge.task.abort();
+        let _ = bridge.task.await;
+        assert_eq!(expect(&mut messages, &topics.status).await, "offline");
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use crate::config::Schedule;
+use crate::i18n::t;
+
+/// スケジュール実行時に表示するデスクトップ通知
+///
+/// Linux等では D-Bus の freedesktop 通知、Windows ではトースト通知、macOS では通知センターを使用する
+#[derive(Debug, Clone)]
+pub struct DesktopNotification {
+    pub title: String,
+    pub body: String,
+}
+
+impl DesktopNotification {
+    /// スケジュールのテンプレートから通知内容を作成
+    pub fn for_schedule(schedule: &Schedule, triggered_at: &DateTime<Local>) -> Self {
+        Self::for_schedule_with(schedule, triggered_at, t)
+    }
+
+    /// `message` で取得したメッセージをデフォルトのテンプレートとして通知内容を作成
+    fn for_schedule_with(schedule: &Schedule, triggered_at: &DateTime<Local>, message: impl Fn(&str) -> String) -> Self {
+        // 未指定の場合は表示言語のデフォルトのテンプレート
+        let template = schedule.notification.clone().unwrap_or_default();
+        let title = template.title.unwrap_or_else(|| message("notification.title"));
+        let body = template.body.unwrap_or_else(|| message("notification.body"));
+
+        Self {
+            title: Self::render(&title, schedule, triggered_at),
+            body: Self::render(&body, schedule, triggered_at),
+        }
+    }
+
+    /// テンプレートのプレースホルダーを置換
+    fn render(template: &str, schedule: &Schedule, triggered_at: &DateTime<Local>) -> String {
+        template
+            .replace("{id}", &schedule.id)
+            .replace("{time}", &triggered_at.format("%H:%M").to_string())
+            .replace("{date}", &triggered_at.format("%Y-%m-%d").to_string())
+    }
+
+    /// 通知を表示（D-Bus呼び出しなどでブロックするため `spawn_blocking` から呼ぶ）
+    pub fn show(&self) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("Tasktray Chime")
+            .summary(&self.title)
+            .body(&self.body)
+            .show()
+            .map(|_| ())
+            .context("Failed to show desktop notification")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotificationTemplate;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn schedule(notification: Option<NotificationTemplate>) -> Schedule {
+        let mut schedule: Schedule = serde_yaml::from_str(
+            "id: hourly_chime\ntype: cron\ncron: \"0 * * * *\"\nenabled: true\ndelivery: notify\n",
+        )
+        .unwrap();
+        schedule.notification = notification;
+        schedule
+    }
+
+    fn triggered_at() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 1, 2, 9, 5, 0).unwrap()
+    }
+
+    #[test]
+    fn render_replaces_all_placeholders() {
+        let rendered = DesktopNotification::render("{date} {time} {id} ({id})", &schedule(None), &triggered_at());
+        assert_eq!(rendered, "2025-01-02 09:05 hourly_chime (hourly_chime)");
+        assert_eq!(DesktopNotification::render("{unknown}", &schedule(None), &triggered_at()), "{unknown}");
+    }
+
+    #[test]
+    fn for_schedule_uses_template() {
+        let template = NotificationTemplate {
+            title: Some("Reminder: {id}".to_string()),
+            body: Some("{date} {time}".to_string()),
+        };
+        let notification = DesktopNotification::for_schedule(&schedule(Some(template)), &triggered_at());
+        assert_eq!(notification.title, "Reminder: hourly_chime");
+        assert_eq!(notification.body, "2025-01-02 09:05");
+    }
+
+    /// 組み込みのメッセージカタログ（グローバルな表示言語を変えずに参照する）
+    fn catalog(language: &str) -> HashMap<String, String> {
+        let content = match language {
+            "ja" => include_str!("../locales/ja.yaml"),
+            "en" => include_str!("../locales/en.yaml"),
+            _ => unreachable!(),
+        };
+        serde_yaml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn for_schedule_falls_back_to_localized_defaults() {
+        // 一部だけ指定した場合は残りが表示言語のデフォルトになる
+        let template = NotificationTemplate { title: Some("{id}".to_string()), body: None };
+        for language in ["ja", "en"] {
+            let catalog = catalog(language);
+            let message = |key: &str| catalog[key].clone();
+            let expected_title = catalog["notification.title"].clone();
+            let expected_body = catalog["notification.body"].replace("{time}", "09:05").replace("{id}", "hourly_chime");
+
+            let notification = DesktopNotification::for_schedule_with(&schedule(None), &triggered_at(), message);
+            assert_eq!(notification.title, expected_title, "language: {}", language);
+            assert_eq!(notification.body, expected_body, "language: {}", language);
+
+            let notification = DesktopNotification::for_schedule_with(&schedule(Some(template.clone())), &triggered_at(), message);
+            assert_eq!(notification.title, "hourly_chime", "language: {}", language);
+            assert_eq!(notification.body, expected_body, "language: {}", language);
+        }
+    }
+}
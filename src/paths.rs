@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+
+/// 設定ファイルのパスを指定する環境変数
+pub const CONFIG_ENV_VAR: &str = "TASKTRAY_CHIME_CONFIG";
+
+/// 探索する設定ファイル名（先に見つかったものを使用）
+const CONFIG_FILE_NAMES: [&str; 4] = ["config.yaml", "config.yml", "config.toml", "config.json"];
+
+/// 設定ファイルがどこで見つかったか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLocation {
+    /// `--config` フラグ
+    CommandLine,
+    /// 環境変数 `TASKTRAY_CHIME_CONFIG`
+    Environment,
+    /// 実行ファイルと同じディレクトリ（ポータブルモード）
+    Portable,
+    /// XDG / AppData などのプラットフォーム標準ディレクトリ
+    Platform,
+}
+
+/// アプリケーションが使用するファイルの配置場所
+#[derive(Debug, Clone)]
+pub struct AppPaths {
+    pub config_file: PathBuf,
+    pub location: ConfigLocation,
+    /// ログ・履歴・状態ファイルの保存先
+    pub data_dir: PathBuf,
+}
+
+impl AppPaths {
+    /// 設定ファイルとデータディレクトリを決定する
+    ///
+    /// 探索順: `--config` → `TASKTRAY_CHIME_CONFIG` → 実行ファイルのディレクトリ → プラットフォーム標準ディレクトリ
+    pub fn resolve(cli_config: Option<PathBuf>) -> Result<Self> {
+        let exe_dir = Self::exe_dir()?;
+        let project_dirs = ProjectDirs::from("", "", "tasktray-chime");
+        let env_config = std::env::var_os(CONFIG_ENV_VAR).filter(|v| !v.is_empty()).map(PathBuf::from);
+        let platform_dirs = project_dirs.as_ref().map(|dirs| (dirs.config_dir(), dirs.data_local_dir()));
+
+        Ok(Self::resolve_in(cli_config, env_config, exe_dir, platform_dirs))
+    }
+
+    /// 探索順に従って設定ファイルとデータディレクトリを決定する
+    ///
+    /// `platform_dirs` はプラットフォーム標準の設定・データディレクトリ（ホームディレクトリが取得できない環境では None）
+    fn resolve_in(
+        cli_config: Option<PathBuf>,
+        env_config: Option<PathBuf>,
+        exe_dir: PathBuf,
+        platform_dirs: Option<(&Path, &Path)>,
+    ) -> Self {
+        // ポータブルモード以外のデータ保存先
+        let platform_data_dir = platform_dirs
+            .map(|(_, data_dir)| data_dir.to_path_buf())
+            .unwrap_or_else(|| exe_dir.clone());
+
+        if let Some(path) = cli_config {
+            return Self {
+                config_file: path,
+                location: ConfigLocation::CommandLine,
+                data_dir: platform_data_dir,
+            };
+        }
+
+        if let Some(path) = env_config {
+            return Self {
+                config_file: path,
+                location: ConfigLocation::Environment,
+                data_dir: platform_data_dir,
+            };
+        }
+
+        if let Some(path) = Self::find_config_in(&exe_dir) {
+            return Self {
+                config_file: path,
+                location: ConfigLocation::Portable,
+                data_dir: exe_dir,
+            };
+        }
+
+        match platform_dirs {
+            Some((config_dir, _)) => {
+                let config_file = Self::find_config_in(config_dir)
+                    .unwrap_or_else(|| config_dir.join(CONFIG_FILE_NAMES[0]));
+                Self {
+                    config_file,
+                    location: ConfigLocation::Platform,
+                    data_dir: platform_data_dir,
+                }
+            }
+            None => {
+                // ホームディレクトリが取得できない環境では実行ファイルのディレクトリを使用
+                Self {
+                    config_file: exe_dir.join(CONFIG_FILE_NAMES[0]),
+                    location: ConfigLocation::Portable,
+                    data_dir: exe_dir,
+                }
+            }
+        }
+    }
+
+    /// データディレクトリ基準でパスを解決（絶対パスはそのまま）
+    pub fn resolve_data_path(&self, path: &str) -> PathBuf {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.data_dir.join(path)
+        }
+    }
+
+    /// スケジュール実行履歴ファイル
+    pub fn history_file(&self) -> PathBuf {
+        self.data_dir.join("history.jsonl")
+    }
+
+    /// 再起動後も保持する状態（スヌーズ・スキップなど）のファイル
+    pub fn state_file(&self) -> PathBuf {
+        self.data_dir.join("state.json")
+    }
+
+    fn exe_dir() -> Result<PathBuf> {
+        let exe_path = std::env::current_exe()
+            .context("Failed to get executable path")?;
+        let exe_dir = exe_path.parent()
+            .context("Failed to get executable directory")?;
+        Ok(exe_dir.to_path_buf())
+    }
+
+    fn find_config_in(dir: &Path) -> Option<PathBuf> {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path) -> PathBuf {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn find_config_in_prefers_names_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(AppPaths::find_config_in(dir.path()), None);
+
+        // ディレクトリは設定ファイルとして扱わない
+        std::fs::create_dir(dir.path().join("config.yaml")).unwrap();
+        let json = touch(&dir.path().join("config.json"));
+        assert_eq!(AppPaths::find_config_in(dir.path()), Some(json));
+        let toml = touch(&dir.path().join("config.toml"));
+        assert_eq!(AppPaths::find_config_in(dir.path()), Some(toml));
+        let yml = touch(&dir.path().join("config.yml"));
+        assert_eq!(AppPaths::find_config_in(dir.path()), Some(yml));
+    }
+
+    #[test]
+    fn resolve_follows_lookup_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe_dir = dir.path().join("bin");
+        let config_dir = dir.path().join("config");
+        let data_dir = dir.path().join("data");
+        let platform_dirs = Some((config_dir.as_path(), data_dir.as_path()));
+        let cli = dir.path().join("cli.yaml");
+        let env = dir.path().join("env.toml");
+        let resolve = |cli: Option<&PathBuf>, env: Option<&PathBuf>| {
+            let paths = AppPaths::resolve_in(cli.cloned(), env.cloned(), exe_dir.clone(), platform_dirs);
+            (paths.config_file, paths.location, paths.data_dir)
+        };
+
+        // どこにも設定ファイルがなければプラットフォーム標準ディレクトリに作成する
+        assert_eq!(resolve(None, None), (config_dir.join("config.yaml"), ConfigLocation::Platform, data_dir.clone()));
+        let platform = touch(&config_dir.join("config.json"));
+        assert_eq!(resolve(None, None), (platform, ConfigLocation::Platform, data_dir.clone()));
+
+        // 実行ファイルのディレクトリにあればポータブルモード（データも同じ場所）
+        let portable = touch(&exe_dir.join("config.toml"));
+        assert_eq!(resolve(None, None), (portable, ConfigLocation::Portable, exe_dir.clone()));
+
+        assert_eq!(resolve(None, Some(&env)), (env.clone(), ConfigLocation::Environment, data_dir.clone()));
+        assert_eq!(resolve(Some(&cli), Some(&env)), (cli, ConfigLocation::CommandLine, data_dir));
+    }
+
+    #[test]
+    fn resolve_without_platform_dirs_uses_exe_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::resolve_in(None, None, dir.path().to_path_buf(), None);
+        assert_eq!(paths.config_file, dir.path().join("config.yaml"));
+        assert_eq!(paths.location, ConfigLocation::Portable);
+        assert_eq!(paths.data_dir, dir.path());
+
+        let cli = dir.path().join("cli.yaml");
+        assert_eq!(AppPaths::resolve_in(Some(cli), None, dir.path().to_path_buf(), None).data_dir, dir.path());
+    }
+
+    #[test]
+    fn data_path_is_resolved_against_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::resolve_in(None, None, dir.path().to_path_buf(), None);
+        assert_eq!(paths.resolve_data_path("logs/chime.log"), dir.path().join("logs/chime.log"));
+
+        let absolute = dir.path().join("elsewhere/chime.log");
+        assert_eq!(paths.resolve_data_path(&absolute.to_string_lossy()), absolute);
+        assert_eq!(paths.history_file(), dir.path().join("history.jsonl"));
+        assert_eq!(paths.state_file(), dir.path().join("state.json"));
+    }
+}
@@ -1,13 +1,19 @@
 use anyhow::Result;
 use chrono::{DateTime, Local, Utc};
 use cron::Schedule as CronSchedule;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinSet;
 use tokio::time::Duration;
-use crate::config::Schedule;
-use crate::audio::AudioPlayer;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::config::{BehaviorConfig, QuietHours, Schedule};
+use crate::audio::{AudioPlayer, PlaybackOptions};
+use crate::hooks::{self, HookContext, HookKind};
+use crate::notification::DesktopNotification;
 
 /// スケジュール実行判定の時間枠（秒）
 /// この時間内に次回実行時刻がある場合、実行対象とする
@@ -17,67 +23,362 @@ const SCHEDULE_EXECUTION_THRESHOLD_SECONDS: i64 = 5;
 /// 前回実行からこの時間が経過していない場合は実行をスキップ
 const DUPLICATE_PREVENTION_INTERVAL_SECONDS: i64 = 30;
 
-#[derive(Debug, Clone)]
+/// スヌーズ・スキップの予定時刻をこの秒数以上過ぎたら破棄する（アプリ停止中に過ぎた場合など）
+const STALE_PENDING_FIRE_SECONDS: i64 = 60;
+
+/// 最短の実行間隔を求めるときに調べる実行予定の数
+const FIRE_INTERVAL_SAMPLES: usize = 32;
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ScheduleEvent {
     pub schedule_id: String,
+    /// 予定時刻（手動実行の場合は実行時刻）
+    pub planned_at: DateTime<Local>,
     pub triggered_at: DateTime<Local>,
+    #[serde(flatten)]
+    pub outcome: ScheduleOutcome,
+}
+
+/// スケジュール実行の結果
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ScheduleOutcome {
+    /// 実行を開始した
+    Fired,
+    /// 再生が完了した（代替音声を再生した場合はその音声）
+    Succeeded {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fallback: Option<String>,
+    },
+    /// 再生に失敗した
+    Failed { error: String },
+    /// 実行時刻だったが実行しなかった
+    Skipped { reason: String },
+}
+
+impl ScheduleOutcome {
+    /// 種類を表す名前（JSON の `outcome` と同じ）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Fired => "fired",
+            Self::Succeeded { .. } => "succeeded",
+            Self::Failed { .. } => "failed",
+            Self::Skipped { .. } => "skipped",
+        }
+    }
+}
+
+/// 再生に失敗したときの再試行（代替音声もすべて失敗した場合に行う）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetryPolicy {
+    /// 再試行する回数
+    pub retries: u32,
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(behavior: &BehaviorConfig) -> Self {
+        Self {
+            retries: behavior.retry_on_fail,
+            delay: Duration::from_secs(behavior.retry_delay_seconds),
+        }
+    }
+}
+
+/// 次に実行されるスケジュール
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NextFire {
+    pub schedule_id: String,
+    pub at: DateTime<Local>,
+}
+
+/// スヌーズによる一回限りの実行予定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OneOffFire {
+    pub schedule_id: String,
+    pub at: DateTime<Local>,
+}
+
+/// スヌーズ・スキップによる実行予定の変更（再起動後も保持される）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PendingFires {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snoozed: Vec<OneOffFire>,
+    /// 実行しない次回実行時刻（スケジュールIDごと）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub skipped: BTreeMap<String, DateTime<Local>>,
+}
+
+impl PendingFires {
+    /// 予定時刻を大きく過ぎたものを破棄
+    fn prune_stale(&mut self, now: &DateTime<Local>) {
+        let limit = *now - chrono::Duration::seconds(STALE_PENDING_FIRE_SECONDS);
+        self.snoozed.retain(|fire| {
+            let keep = fire.at >= limit;
+            if !keep {
+                tracing::info!(schedule_id = %fire.schedule_id, planned_at = %fire.at.to_rfc3339(), "Dropping missed snooze");
+            }
+            keep
+        });
+        self.skipped.retain(|_, at| *at >= limit);
+    }
 }
 
 pub struct CronScheduler {
-    schedules: HashMap<String, Schedule>,
+    schedules: Arc<Mutex<HashMap<String, Schedule>>>,
+    /// スケジュール変更時に待機中のサイクルを起こす
+    reload_notify: Arc<Notify>,
     audio_player: Arc<AudioPlayer>,
     event_sender: Option<mpsc::UnboundedSender<ScheduleEvent>>,
-    shutdown_sender: Option<tokio::sync::oneshot::Sender<()>>,
+    /// スケジューラーのタスクを停止する
+    cancel: Option<CancellationToken>,
     start_time: DateTime<Local>,
     last_executed: Arc<Mutex<HashMap<String, DateTime<Local>>>>,
+    /// 次回実行予定（スケジューラーのサイクルごとに更新）
+    next_fire: watch::Sender<Option<NextFire>>,
+    controls: RunControls,
+}
+
+/// 実行するかどうかの判定に使う状態（スケジューラーのタスクと共有）
+#[derive(Clone)]
+struct RunControls {
+    quiet_hours: Arc<Mutex<Option<QuietHours>>>,
+    pending: Arc<Mutex<PendingFires>>,
+    /// 一時停止の終了時刻（一時停止中でなければ None）
+    paused_until: watch::Sender<Option<DateTime<Local>>>,
+    /// 実行中の再生・通知（終了時に完了を待つ）
+    playbacks: Arc<Mutex<JoinSet<()>>>,
+    retry: Arc<Mutex<RetryPolicy>>,
 }
 
 impl CronScheduler {
     pub fn new(audio_player: Arc<AudioPlayer>) -> Self {
         Self {
-            schedules: HashMap::new(),
+            schedules: Arc::new(Mutex::new(HashMap::new())),
+            reload_notify: Arc::new(Notify::new()),
             audio_player,
             event_sender: None,
-            shutdown_sender: None,
+            cancel: None,
             start_time: Local::now(),
             last_executed: Arc::new(Mutex::new(HashMap::new())),
+            next_fire: watch::Sender::new(None),
+            controls: RunControls {
+                quiet_hours: Arc::new(Mutex::new(None)),
+                pending: Arc::new(Mutex::new(PendingFires::default())),
+                paused_until: watch::Sender::new(None),
+                playbacks: Arc::new(Mutex::new(JoinSet::new())),
+                retry: Arc::new(Mutex::new(RetryPolicy::default())),
+            },
+        }
+    }
+
+    /// 指定時刻まで全スケジュールを一時停止（終了時刻に自動で再開）
+    pub fn pause_until(&self, until: DateTime<Local>) {
+        self.controls.paused_until.send_replace(Some(until));
+        tracing::info!(paused_until = %until.to_rfc3339(), "Paused all schedules");
+        self.reload_notify.notify_one();
+    }
+
+    /// 保存されていた一時停止を復元（終了時刻を過ぎていれば何もしない）
+    pub fn restore_paused_until(&self, until: Option<DateTime<Local>>) {
+        match until {
+            Some(until) if until > Local::now() => self.pause_until(until),
+            Some(until) => tracing::info!(paused_until = %until.to_rfc3339(), "Saved pause has already ended, not restoring"),
+            None => {}
         }
     }
 
+    /// 一時停止を解除
+    pub fn resume(&self) {
+        if self.controls.paused_until.send_replace(None).is_some() {
+            tracing::info!("Resumed all schedules");
+        }
+        self.reload_notify.notify_one();
+    }
+
+    /// 一時停止の終了時刻の変更を受け取る（自動再開を含む）
+    pub fn subscribe_paused_until(&self) -> watch::Receiver<Option<DateTime<Local>>> {
+        self.controls.paused_until.subscribe()
+    }
+
+    /// 一時停止の終了時刻（一時停止中でなければ None）
+    pub fn paused_until(&self) -> Option<DateTime<Local>> {
+        *self.controls.paused_until.borrow()
+    }
+
+    /// 静音時間帯を設定
+    pub fn set_quiet_hours(&self, quiet_hours: Option<QuietHours>) {
+        *self.controls.quiet_hours.lock().unwrap_or_else(|e| e.into_inner()) = quiet_hours;
+        self.reload_notify.notify_one();
+    }
+
+    /// 再生に失敗したときの再試行を設定
+    pub fn set_retry_policy(&self, retry: RetryPolicy) {
+        *self.controls.retry.lock().unwrap_or_else(|e| e.into_inner()) = retry;
+    }
+
+    /// 次回実行予定の変更を受け取る
+    pub fn subscribe_next_fire(&self) -> watch::Receiver<Option<NextFire>> {
+        self.next_fire.subscribe()
+    }
+
+    /// 現在の次回実行予定
+    pub fn next_fire(&self) -> Option<NextFire> {
+        self.next_fire.borrow().clone()
+    }
+
+    /// スケジュールを指定分数後にもう一度実行する
+    pub fn snooze(&self, schedule_id: &str, minutes: u32) -> Result<DateTime<Local>> {
+        if !self.lock_schedules().contains_key(schedule_id) {
+            return Err(anyhow::anyhow!("Unknown schedule: {}", schedule_id));
+        }
+
+        let at = Local::now() + chrono::Duration::minutes(minutes as i64);
+        self.lock_pending().snoozed.push(OneOffFire {
+            schedule_id: schedule_id.to_string(),
+            at,
+        });
+        tracing::info!(schedule_id, planned_at = %at.to_rfc3339(), "Snoozed schedule");
+        self.reload_notify.notify_one();
+        Ok(at)
+    }
+
+    /// スケジュールの次回実行をスキップする
+    pub fn skip_next(&self, schedule_id: &str) -> Result<DateTime<Local>> {
+        let schedule = self.lock_schedules()
+            .get(schedule_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown schedule: {}", schedule_id))?;
+        if !schedule.enabled {
+            return Err(anyhow::anyhow!("Schedule '{}' is disabled", schedule_id));
+        }
+
+        let at = Self::next_fire_time(&schedule, &Local::now())
+            .ok_or_else(|| anyhow::anyhow!("Schedule '{}' has no next run time", schedule_id))?;
+        self.lock_pending().skipped.insert(schedule_id.to_string(), at);
+        tracing::info!(schedule_id, planned_at = %at.to_rfc3339(), "Skipping next run of schedule");
+        self.reload_notify.notify_one();
+        Ok(at)
+    }
+
+    /// 次回実行のスキップを取り消す
+    pub fn cancel_skip(&self, schedule_id: &str) -> bool {
+        let cancelled = self.lock_pending().skipped.remove(schedule_id).is_some();
+        if cancelled {
+            tracing::info!(schedule_id, "Cancelled skip of schedule");
+            self.reload_notify.notify_one();
+        }
+        cancelled
+    }
+
+    /// 現在のスヌーズ・スキップ予定
+    pub fn pending_fires(&self) -> PendingFires {
+        self.lock_pending().clone()
+    }
+
+    /// 保存されていたスヌーズ・スキップ予定を復元（過ぎた予定は破棄）
+    pub fn restore_pending_fires(&self, mut pending: PendingFires) {
+        pending.prune_stale(&Local::now());
+        tracing::info!(
+            "Restored {} snooze(s) and {} skip(s)",
+            pending.snoozed.len(),
+            pending.skipped.len()
+        );
+        *self.lock_pending() = pending;
+        self.reload_notify.notify_one();
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, PendingFires> {
+        self.controls.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// スケジュールを追加/更新
     pub fn add_schedule(&mut self, schedule: Schedule) -> Result<()> {
         // cron式の妥当性をチェック
         Self::validate_cron_expression(&schedule.cron)?;
         
-        tracing::info!("Adding schedule: {} with cron: {}", schedule.id, schedule.cron);
-        self.schedules.insert(schedule.id.clone(), schedule);
+        tracing::info!(schedule_id = %schedule.id, cron = %schedule.cron, "Adding schedule");
+        self.lock_schedules().insert(schedule.id.clone(), schedule);
+        self.reload_notify.notify_one();
         Ok(())
     }
 
+    /// 全スケジュールを置き換える（設定再読み込み用）
+    ///
+    /// cron式が不正なスケジュールはエラーをログに出力してスキップする
+    pub fn replace_schedules(&self, schedules: Vec<Schedule>) {
+        let mut valid = HashMap::new();
+        for schedule in schedules {
+            match Self::validate_cron_expression(&schedule.cron) {
+                Ok(()) => {
+                    valid.insert(schedule.id.clone(), schedule);
+                }
+                Err(e) => {
+                    tracing::error!(schedule_id = %schedule.id, error = %e, "Skipping schedule on reload");
+                }
+            }
+        }
+
+        tracing::info!("Replacing schedules: {} schedule(s) active", valid.len());
+        *self.lock_schedules() = valid;
+        self.reload_notify.notify_one();
+    }
+
+    fn lock_schedules(&self) -> std::sync::MutexGuard<'_, HashMap<String, Schedule>> {
+        self.schedules.lock().unwrap_or_else(|e| {
+            tracing::warn!("Mutex poisoned while accessing schedules - recovering by using poisoned data. A panic may have occurred in another thread.");
+            e.into_inner()
+        })
+    }
+
     /// スケジュールを削除
     /// スケジューラーを開始
-    pub async fn start(&mut self) -> Result<mpsc::UnboundedReceiver<ScheduleEvent>> {
+    ///
+    /// `shutdown` がキャンセルされるか `stop` を呼ぶとスケジューラーのタスクを終了する
+    pub async fn start(&mut self, shutdown: CancellationToken) -> Result<mpsc::UnboundedReceiver<ScheduleEvent>> {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
+        let cancel = shutdown.child_token();
 
         self.event_sender = Some(event_tx.clone());
-        self.shutdown_sender = Some(shutdown_tx);
+        self.cancel = Some(cancel.clone());
 
         let schedules = self.schedules.clone();
+        let reload_notify = self.reload_notify.clone();
         let audio_player = self.audio_player.clone();
         let start_time = self.start_time;
         let last_executed = self.last_executed.clone();
+        let next_fire = self.next_fire.clone();
+        let controls = self.controls.clone();
 
         tokio::spawn(async move {
-            tracing::info!("Cron scheduler started with {} schedules", schedules.len());
+            tracing::info!(
+                "Cron scheduler started with {} schedules",
+                schedules.lock().map(|s| s.len()).unwrap_or_default()
+            );
             
             loop {
+                // 各サイクルの開始時点のスケジュールを使用
+                let snapshot = schedules
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone();
+                Self::publish_next_fire(&snapshot, &controls.pending, &next_fire);
+                let resume_at = *controls.paused_until.borrow();
+
                 tokio::select! {
-                    _ = &mut shutdown_rx => {
+                    _ = cancel.cancelled() => {
                         tracing::info!("Cron scheduler shutdown requested");
                         break;
                     }
-                    _ = Self::run_scheduler_cycle(&schedules, &audio_player, &event_tx, start_time, &last_executed) => {
+                    _ = reload_notify.notified() => {
+                        tracing::debug!("Schedules changed, recalculating next run time");
+                    }
+                    _ = Self::sleep_until(resume_at) => {
+                        // 一時停止の終了時刻に自動で再開
+                        controls.paused_until.send_replace(None);
+                        tracing::info!("Pause ended, resuming all schedules");
+                    }
+                    _ = Self::run_scheduler_cycle(&snapshot, &audio_player, &event_tx, start_time, &last_executed, &controls) => {
                         // スケジューラーサイクル完了後、短い間隔で再チェック
                         tokio::time::sleep(Duration::from_millis(100)).await;
                     }
@@ -92,12 +393,48 @@ impl CronScheduler {
 
     /// スケジューラーを停止
     pub fn stop(&mut self) {
-        if let Some(shutdown_tx) = self.shutdown_sender.take() {
-            let _ = shutdown_tx.send(());
+        if let Some(cancel) = self.cancel.take() {
+            cancel.cancel();
             tracing::info!("Cron scheduler stop signal sent");
         }
     }
 
+    /// スケジューラーを停止し、実行中の再生・通知の完了を最大 `grace` まで待つ
+    ///
+    /// 猶予時間を過ぎても終わらない再生は停止する（結果は `Failed` として送信される）
+    pub async fn shutdown(&mut self, grace: Duration) {
+        self.stop();
+
+        let mut playbacks = std::mem::take(&mut *self.controls.playbacks.lock().unwrap_or_else(|e| e.into_inner()));
+        if playbacks.is_empty() {
+            return;
+        }
+
+        tracing::info!("Waiting up to {:?} for {} playback(s) to finish", grace, playbacks.len());
+        if tokio::time::timeout(grace, Self::join_all(&mut playbacks)).await.is_ok() {
+            tracing::info!("All playbacks finished");
+            return;
+        }
+
+        tracing::warn!("Shutdown grace period elapsed, stopping {} playback(s)", playbacks.len());
+        self.audio_player.shutdown();
+        // 通知の表示待ちなど停止できないものは打ち切る
+        if tokio::time::timeout(Duration::from_secs(1), Self::join_all(&mut playbacks)).await.is_err() {
+            tracing::warn!("Aborting {} unfinished task(s)", playbacks.len());
+            playbacks.abort_all();
+        }
+    }
+
+    async fn join_all(playbacks: &mut JoinSet<()>) {
+        while let Some(result) = playbacks.join_next().await {
+            if let Err(e) = result
+                && e.is_panic()
+            {
+                tracing::error!("Playback task panicked: {}", e);
+            }
+        }
+    }
+
     /// スケジューラーサイクルを実行
     async fn run_scheduler_cycle(
         schedules: &HashMap<String, Schedule>,
@@ -105,7 +442,9 @@ impl CronScheduler {
         event_tx: &mpsc::UnboundedSender<ScheduleEvent>,
         start_time: DateTime<Local>,
         last_executed: &Arc<Mutex<HashMap<String, DateTime<Local>>>>,
+        controls: &RunControls,
     ) {
+        let RunControls { quiet_hours, pending, paused_until, playbacks, retry } = controls;
         let now = Local::now();
         
         // 起動から10秒以内は実行をスキップ（意図しない起動時実行を防ぐ）
@@ -147,19 +486,34 @@ impl CronScheduler {
                             }
                         };
                         
-                        if should_execute {
+                        if should_execute && Self::take_skipped(pending, &schedule.id, &next_time) {
+                            // ユーザーの指定により今回の実行をスキップ
+                            tracing::info!(
+                                schedule_id = %schedule.id,
+                                planned_at = %next_time.to_rfc3339(),
+                                outcome = "skipped",
+                                reason = "skipped_by_user",
+                                "Skipping schedule as requested"
+                            );
+                            last_executed
+                                .lock()
+                                .unwrap_or_else(|e| e.into_inner())
+                                .insert(schedule.id.clone(), now);
+                            Self::send_event(event_tx, &schedule.id, next_time, now, ScheduleOutcome::Skipped {
+                                reason: "skipped_by_user".to_string(),
+                            });
+                        } else if should_execute {
                             tracing::info!(
-                                "Schedule '{}' ready for execution at {} (cron: {}), time diff: {} seconds",
-                                schedule.id,
-                                next_time.format("%Y-%m-%d %H:%M:%S"),
-                                schedule.cron,
-                                time_diff.num_seconds()
+                                schedule_id = %schedule.id,
+                                planned_at = %next_time.to_rfc3339(),
+                                cron = %schedule.cron,
+                                "Schedule ready for execution"
                             );
-                            schedules_to_execute.push(schedule.clone());
+                            schedules_to_execute.push((schedule.clone(), next_time));
                         } else {
                             tracing::debug!(
-                                "Skipping duplicate execution of schedule '{}' (already executed recently)",
-                                schedule.id
+                                schedule_id = %schedule.id,
+                                "Skipping duplicate execution (already executed recently)"
                             );
                         }
                     }
@@ -175,17 +529,41 @@ impl CronScheduler {
                     }
                 }
                 Err(e) => {
-                    tracing::error!("Failed to calculate next run time for schedule '{}': {}", schedule.id, e);
+                    tracing::error!(schedule_id = %schedule.id, error = %e, "Failed to calculate next run time");
+                }
+            }
+        }
+
+        // スヌーズによる一回限りの実行
+        let mut snoozed_to_execute = Vec::new();
+        {
+            let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.prune_stale(&now);
+            pending.snoozed.retain(|fire| {
+                if fire.at.signed_duration_since(now).num_seconds() > SCHEDULE_EXECUTION_THRESHOLD_SECONDS {
+                    return true;
+                }
+                match schedules.get(&fire.schedule_id) {
+                    Some(schedule) => snoozed_to_execute.push((schedule.clone(), fire.at)),
+                    None => tracing::warn!(schedule_id = %fire.schedule_id, "Dropping snooze for unknown schedule"),
                 }
+                false
+            });
+            if let Some(next_snooze) = pending.snoozed.iter().map(|fire| fire.at).min() {
+                next_run_time = Some(next_run_time.map_or(next_snooze, |t| t.min(next_snooze)));
             }
         }
 
         // 実行対象のスケジュールを実行
-        for schedule in schedules_to_execute {
+        let cron_fires = schedules_to_execute.into_iter().map(|(s, at)| (s, at, false));
+        let snoozed_fires = snoozed_to_execute.into_iter().map(|(s, at)| (s, at, true));
+        for (schedule, planned_at, snoozed) in cron_fires.chain(snoozed_fires) {
             let now_exec = Local::now();
+            // 予定時刻からの遅れ（予定時刻の少し前に実行した場合は負）
+            let latency_ms = now_exec.signed_duration_since(planned_at).num_milliseconds();
             
-            // 最後の実行時刻を記録
-            {
+            // 最後の実行時刻を記録（スヌーズは重複判定の対象外）
+            if !snoozed {
                 let mut last_exec_map = last_executed.lock().unwrap_or_else(|e| {
                     tracing::warn!("Mutex poisoned while recording execution time - recovering by using poisoned data. A panic may have occurred in another thread.");
                     e.into_inner()
@@ -193,39 +571,51 @@ impl CronScheduler {
                 last_exec_map.insert(schedule.id.clone(), now_exec);
             }
             
+            // 一時停止中は実行しない
+            if paused_until.borrow().is_some_and(|until| now_exec < until) {
+                tracing::info!(
+                    schedule_id = %schedule.id,
+                    planned_at = %planned_at.to_rfc3339(),
+                    outcome = "skipped",
+                    reason = "paused",
+                    "Skipping schedule while paused"
+                );
+                Self::send_event(event_tx, &schedule.id, planned_at, now_exec, ScheduleOutcome::Skipped {
+                    reason: "paused".to_string(),
+                });
+                continue;
+            }
+
+            // 静音時間帯は実行しない
+            let in_quiet_hours = quiet_hours
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .is_some_and(|q| q.contains(now_exec.time()));
+            if in_quiet_hours {
+                tracing::info!(
+                    schedule_id = %schedule.id,
+                    planned_at = %planned_at.to_rfc3339(),
+                    outcome = "skipped",
+                    reason = "quiet_hours",
+                    "Skipping schedule during quiet hours"
+                );
+                Self::send_event(event_tx, &schedule.id, planned_at, now_exec, ScheduleOutcome::Skipped {
+                    reason: "quiet_hours".to_string(),
+                });
+                continue;
+            }
+
             tracing::info!(
-                "Executing schedule '{}' at {} (cron: {})",
-                schedule.id,
-                now_exec.format("%Y-%m-%d %H:%M:%S"),
-                schedule.cron
+                schedule_id = %schedule.id,
+                planned_at = %planned_at.to_rfc3339(),
+                latency_ms,
+                trigger = if snoozed { "snooze" } else { "cron" },
+                "Executing schedule"
             );
 
-            // 音声再生
-            let audio_player_clone = audio_player.clone();
-            let file_path = schedule.file.clone();
-            let schedule_id = schedule.id.clone();
-            
-            tokio::spawn(async move {
-                tracing::info!("Starting audio playback for schedule '{}': {}", schedule_id, file_path);
-                match audio_player_clone.play_sound(&file_path).await {
-                    Ok(()) => {
-                        tracing::info!("Successfully completed audio playback for schedule '{}'", schedule_id);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to play sound for schedule '{}': {}", schedule_id, e);
-                    }
-                }
-            });
-
-            // イベント送信
-            let event = ScheduleEvent {
-                schedule_id: schedule.id.clone(),
-                triggered_at: now_exec,
-            };
-            
-            if let Err(e) = event_tx.send(event) {
-                tracing::warn!("Failed to send schedule event: {}", e);
-            }
+            let retry = *retry.lock().unwrap_or_else(|e| e.into_inner());
+            Self::execute_schedule(&schedule, audio_player, event_tx, planned_at, now_exec, playbacks, retry);
         }
 
         // 次回実行時間まで待機
@@ -248,11 +638,244 @@ impl CronScheduler {
                 tokio::time::sleep(Duration::from_millis(1000)).await;
             }
         } else {
-            // アクティブなスケジュールがない場合は1秒待機
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            // アクティブなスケジュールがない場合はスケジュールの変更（reload_notify）まで待機
+            std::future::pending::<()>().await;
         }
     }
 
+    /// スケジュールを実行（音声再生・デスクトップ通知・フックを開始し、イベントを送信）
+    ///
+    /// 開始時に `Fired`、完了後に `Succeeded` または `Failed` を送信する
+    /// 音声の再生に失敗した場合は代替音声を順に試し、すべて失敗したら `retry` に従って最初からやり直す
+    /// `on_fire` は再生・通知と並行して実行し、失敗した場合は `Failed` とする。`on_success` / `on_failure` の失敗はログのみ
+    fn execute_schedule(
+        schedule: &Schedule,
+        audio_player: &Arc<AudioPlayer>,
+        event_tx: &mpsc::UnboundedSender<ScheduleEvent>,
+        planned_at: DateTime<Local>,
+        triggered_at: DateTime<Local>,
+        playbacks: &Mutex<JoinSet<()>>,
+        retry: RetryPolicy,
+    ) {
+        Self::send_event(event_tx, &schedule.id, planned_at, triggered_at, ScheduleOutcome::Fired);
+
+        let audio_player_clone = audio_player.clone();
+        let file_path = schedule.sound_path().to_path_buf();
+        let playback_options = PlaybackOptions::for_schedule(schedule);
+        let fallbacks = schedule.resolved_fallback.clone();
+        let schedule_id = schedule.id.clone();
+        let delivery = schedule.delivery;
+        let notification = DesktopNotification::for_schedule(schedule, &triggered_at);
+        let hook_context = HookContext::new(schedule, triggered_at);
+        let (on_fire, on_success, on_failure) = (schedule.on_fire.clone(), schedule.on_success.clone(), schedule.on_failure.clone());
+        let event_tx = event_tx.clone();
+        // 再生・通知・フックのログにスケジュールIDを付ける
+        let span = tracing::info_span!("schedule", schedule_id = %schedule.id, triggered_at = %triggered_at.to_rfc3339());
+
+        let mut playbacks = playbacks.lock().unwrap_or_else(|e| e.into_inner());
+        // 完了済みのタスクを回収
+        while playbacks.try_join_next().is_some() {}
+        playbacks.spawn(async move {
+            let on_fire_task = on_fire.map(|hook| {
+                let context = hook_context.clone();
+                tokio::spawn(async move { hooks::run(&hook, HookKind::Fire, &context, None).await })
+            });
+            let mut errors = Vec::new();
+            let mut fallback_used = None;
+
+            // デスクトップ通知
+            if delivery.notifies() {
+                tracing::info!(title = %notification.title, "Showing desktop notification");
+                let result = tokio::task::spawn_blocking(move || notification.show())
+                    .await
+                    .unwrap_or_else(|e| Err(anyhow::anyhow!("Notification task failed: {}", e)));
+                if let Err(e) = result {
+                    tracing::error!(error = %format!("{:#}", e), "Failed to show notification");
+                    errors.push(format!("{:#}", e));
+                }
+            }
+
+            // 音声再生
+            if delivery.plays_sound() {
+                tracing::info!(file = %file_path.display(), "Starting audio playback");
+                let playback_started = std::time::Instant::now();
+                let mut attempt = 0;
+                let result = loop {
+                    match audio_player_clone.play_with_fallback(&file_path, &fallbacks, &playback_options).await {
+                        Err(e) if attempt < retry.retries && !audio_player_clone.is_shutting_down() => {
+                            attempt += 1;
+                            tracing::warn!(
+                                attempt,
+                                retries = retry.retries,
+                                delay_ms = retry.delay.as_millis() as u64,
+                                error = %format!("{:#}", e),
+                                "Failed to play sound, retrying"
+                            );
+                            tokio::time::sleep(retry.delay).await;
+                        }
+                        result => break result,
+                    }
+                };
+                match result {
+                    Ok(fallback) => {
+                        fallback_used = fallback.map(|fallback| fallback.to_string());
+                        tracing::info!(
+                            outcome = "succeeded",
+                            duration_ms = playback_started.elapsed().as_millis() as u64,
+                            fallback = fallback_used.as_deref(),
+                            "Audio playback completed"
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            outcome = "failed",
+                            duration_ms = playback_started.elapsed().as_millis() as u64,
+                            error = %format!("{:#}", e),
+                            "Failed to play sound"
+                        );
+                        errors.push(format!("{:#}", e));
+                    }
+                }
+            }
+
+            if let Some(task) = on_fire_task {
+                let result = task
+                    .await
+                    .unwrap_or_else(|e| Err(anyhow::anyhow!("Hook task failed: {}", e)));
+                if let Err(e) = result {
+                    tracing::error!(error = %format!("{:#}", e), "on_fire hook failed");
+                    errors.push(format!("{:#}", e));
+                }
+            }
+
+            let outcome = if errors.is_empty() {
+                ScheduleOutcome::Succeeded { fallback: fallback_used }
+            } else {
+                ScheduleOutcome::Failed { error: errors.join("; ") }
+            };
+
+            let after_hook = match &outcome {
+                ScheduleOutcome::Failed { error } => on_failure.map(|hook| (hook, HookKind::Failure, Some(error.clone()))),
+                _ => on_success.map(|hook| (hook, HookKind::Success, None)),
+            };
+            Self::send_event(&event_tx, &schedule_id, planned_at, triggered_at, outcome);
+
+            if let Some((hook, kind, error)) = after_hook
+                && let Err(e) = hooks::run(&hook, kind, &hook_context, error.as_deref()).await
+            {
+                tracing::error!(error = %format!("{:#}", e), "Hook failed");
+            }
+        }.instrument(span));
+    }
+
+    fn send_event(
+        event_tx: &mpsc::UnboundedSender<ScheduleEvent>,
+        schedule_id: &str,
+        planned_at: DateTime<Local>,
+        triggered_at: DateTime<Local>,
+        outcome: ScheduleOutcome,
+    ) {
+        let event = ScheduleEvent {
+            schedule_id: schedule_id.to_string(),
+            planned_at,
+            triggered_at,
+            outcome,
+        };
+        
+        if let Err(e) = event_tx.send(event) {
+            tracing::warn!("Failed to send schedule event: {}", e);
+        }
+    }
+
+    /// 指定時刻まで待機（None の場合は無期限）
+    async fn sleep_until(at: Option<DateTime<Local>>) {
+        match at {
+            Some(at) => {
+                let wait = at.signed_duration_since(Local::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// スキップ予定の実行時刻であれば取り除いて true を返す
+    fn take_skipped(pending: &Arc<Mutex<PendingFires>>, schedule_id: &str, at: &DateTime<Local>) -> bool {
+        let mut pending = pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.skipped.get(schedule_id) == Some(at) {
+            pending.skipped.remove(schedule_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 有効なスケジュールとスヌーズのうち最も早い次回実行予定を公開
+    fn publish_next_fire(
+        schedules: &HashMap<String, Schedule>,
+        pending: &Arc<Mutex<PendingFires>>,
+        next_fire: &watch::Sender<Option<NextFire>>,
+    ) {
+        let now = Local::now();
+        let pending = pending.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let cron_fires = schedules
+            .values()
+            .filter(|s| s.enabled)
+            .filter_map(|s| {
+                let mut at = Self::next_fire_time(s, &now)?;
+                // スキップ予定ならその次の実行時刻
+                if pending.skipped.get(&s.id) == Some(&at) {
+                    at = Self::next_fire_time(s, &(at + chrono::Duration::seconds(1)))?;
+                }
+                Some(NextFire {
+                    schedule_id: s.id.clone(),
+                    at,
+                })
+            });
+        let snoozed_fires = pending.snoozed.iter().map(|fire| NextFire {
+            schedule_id: fire.schedule_id.clone(),
+            at: fire.at,
+        });
+        let earliest = cron_fires.chain(snoozed_fires).min_by_key(|n| n.at);
+
+        next_fire.send_if_modified(|current| {
+            if *current != earliest {
+                *current = earliest;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// スケジュールを今すぐ実行（無効なスケジュールも実行可能）
+    pub fn trigger(&self, schedule_id: &str) -> Result<()> {
+        let schedule = self.lock_schedules()
+            .get(schedule_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown schedule: {}", schedule_id))?;
+        let event_tx = self.event_sender
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Scheduler is not running"))?;
+
+        let now = Local::now();
+        tracing::info!(schedule_id = %schedule.id, trigger = "manual", "Executing schedule");
+        let retry = *self.controls.retry.lock().unwrap_or_else(|e| e.into_inner());
+        Self::execute_schedule(&schedule, &self.audio_player, event_tx, now, now, &self.controls.playbacks, retry);
+        Ok(())
+    }
+
+    /// スケジュールの次回実行時刻（cron式が不正な場合は None）
+    pub fn next_fire_time(schedule: &Schedule, from: &DateTime<Local>) -> Option<DateTime<Local>> {
+        Self::get_next_run_time(&schedule.cron, from).ok()
+    }
+
+    /// 直近の実行予定から求めた最短の実行間隔（cron式が不正な場合や実行予定が1回以下の場合は None）
+    pub fn min_fire_interval(schedule: &Schedule, from: &DateTime<Local>) -> Option<chrono::Duration> {
+        let cron = CronSchedule::from_str(&schedule.cron).ok()?;
+        let upcoming: Vec<_> = cron.after(&from.with_timezone(&Utc)).take(FIRE_INTERVAL_SAMPLES).collect();
+        upcoming.windows(2).map(|pair| pair[1] - pair[0]).min()
+    }
+
     /// 次回実行時間を計算
     fn get_next_run_time(cron_expr: &str, from: &DateTime<Local>) -> Result<DateTime<Local>> {
         let schedule = CronSchedule::from_str(cron_expr)
@@ -276,4 +899,355 @@ impl CronScheduler {
             .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", cron_expr, e))?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AudioConfig, Delivery, FallbackSound};
+    use crate::state::{AppState, StateStore};
+
+    fn audio_player() -> Arc<AudioPlayer> {
+        Arc::new(AudioPlayer::silent(&AudioConfig {
+            global_volume: 50,
+            stream_threshold_mb: 8,
+            sounds_dir: None,
+            fallback_sound: None,
+        }))
+    }
+
+    /// 手動実行・スヌーズでのみ実行されるスケジュール（毎年1月1日 0:00）
+    fn schedule(id: &str, delivery: Delivery) -> Schedule {
+        let mut schedule: Schedule = serde_yaml::from_str(&format!(
+            "id: {}\ntype: cron\ncron: \"0 0 0 1 1 *\"\nenabled: true\n",
+            id
+        ))
+        .unwrap();
+        schedule.delivery = delivery;
+        schedule
+    }
+
+    /// 指定したスケジュールの完了（成功・失敗）イベントを待つ
+    async fn wait_for_result(events: &mut mpsc::UnboundedReceiver<ScheduleEvent>, schedule_id: &str) -> ScheduleEvent {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let event = events.recv().await.expect("scheduler stopped");
+                if event.schedule_id == schedule_id
+                    && matches!(event.outcome, ScheduleOutcome::Succeeded { .. } | ScheduleOutcome::Failed { .. })
+                {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for schedule result")
+    }
+
+    /// 起動直後の実行抑止を受けないよう起動時刻を過去にして開始する
+    async fn start_scheduler(schedules: Vec<Schedule>) -> (CronScheduler, mpsc::UnboundedReceiver<ScheduleEvent>) {
+        let mut scheduler = CronScheduler::new(audio_player());
+        scheduler.start_time = Local::now() - chrono::Duration::minutes(1);
+        for schedule in schedules {
+            scheduler.add_schedule(schedule).unwrap();
+        }
+        let events = scheduler.start(CancellationToken::new()).await.unwrap();
+        (scheduler, events)
+    }
+
+    async fn next_event(events: &mut mpsc::UnboundedReceiver<ScheduleEvent>) -> ScheduleEvent {
+        tokio::time::timeout(Duration::from_secs(10), events.recv())
+            .await
+            .expect("timed out waiting for schedule event")
+            .expect("scheduler stopped")
+    }
+
+    /// 指定した時刻（秒単位）にだけ実行される cron 式（cron 式は UTC で評価される）
+    fn cron_at(at: DateTime<Local>) -> String {
+        use chrono::{Datelike, Timelike};
+        let at = at.with_timezone(&Utc);
+        format!("{} {} {} {} {} *", at.second(), at.minute(), at.hour(), at.day(), at.month())
+    }
+
+    fn seconds_ago(now: DateTime<Local>, seconds: i64) -> DateTime<Local> {
+        now - chrono::Duration::seconds(seconds)
+    }
+
+    #[tokio::test]
+    async fn snooze_adds_one_off_fire() {
+        let mut scheduler = CronScheduler::new(audio_player());
+        scheduler.add_schedule(schedule("standup", Delivery::None)).unwrap();
+
+        let before = Local::now();
+        let at = scheduler.snooze("standup", 5).unwrap();
+        assert!(at >= before + chrono::Duration::minutes(5) && at <= Local::now() + chrono::Duration::minutes(5));
+        assert_eq!(scheduler.pending_fires().snoozed, [OneOffFire { schedule_id: "standup".to_string(), at }]);
+
+        assert!(scheduler.snooze("unknown", 5).is_err());
+        assert_eq!(scheduler.pending_fires().snoozed.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn skip_next_records_next_fire_and_can_be_cancelled() {
+        let mut scheduler = CronScheduler::new(audio_player());
+        scheduler.add_schedule(schedule("standup", Delivery::None)).unwrap();
+        let mut disabled = schedule("disabled", Delivery::None);
+        disabled.enabled = false;
+        scheduler.add_schedule(disabled).unwrap();
+
+        let at = scheduler.skip_next("standup").unwrap();
+        let expected = CronScheduler::next_fire_time(&schedule("standup", Delivery::None), &Local::now()).unwrap();
+        assert_eq!(at, expected);
+        assert_eq!(scheduler.pending_fires().skipped.get("standup"), Some(&at));
+
+        assert!(scheduler.skip_next("disabled").is_err());
+        assert!(scheduler.skip_next("unknown").is_err());
+
+        assert!(scheduler.cancel_skip("standup"));
+        assert!(!scheduler.cancel_skip("standup"));
+        assert!(scheduler.pending_fires().skipped.is_empty());
+        scheduler.stop();
+    }
+
+    #[test]
+    fn prune_stale_drops_long_past_fires() {
+        let now = Local::now();
+        let stale = seconds_ago(now, STALE_PENDING_FIRE_SECONDS + 1);
+        let recent = seconds_ago(now, STALE_PENDING_FIRE_SECONDS - 1);
+        let mut pending = PendingFires {
+            snoozed: vec![
+                OneOffFire { schedule_id: "stale".to_string(), at: stale },
+                OneOffFire { schedule_id: "recent".to_string(), at: recent },
+            ],
+            skipped: BTreeMap::from([("stale".to_string(), stale), ("recent".to_string(), recent)]),
+        };
+
+        pending.prune_stale(&now);
+        assert_eq!(pending.snoozed, [OneOffFire { schedule_id: "recent".to_string(), at: recent }]);
+        assert_eq!(pending.skipped, BTreeMap::from([("recent".to_string(), recent)]));
+    }
+
+    #[test]
+    fn take_skipped_matches_only_the_skipped_time() {
+        let at = Local::now();
+        let pending = Arc::new(Mutex::new(PendingFires {
+            skipped: BTreeMap::from([("standup".to_string(), at)]),
+            ..PendingFires::default()
+        }));
+
+        assert!(!CronScheduler::take_skipped(&pending, "standup", &(at + chrono::Duration::seconds(1))));
+        assert!(!CronScheduler::take_skipped(&pending, "other", &at));
+        assert!(CronScheduler::take_skipped(&pending, "standup", &at));
+        // 一度スキップしたら取り除かれる
+        assert!(!CronScheduler::take_skipped(&pending, "standup", &at));
+    }
+
+    #[tokio::test]
+    async fn pending_fires_survive_restart_through_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        let mut scheduler = CronScheduler::new(audio_player());
+        scheduler.add_schedule(schedule("standup", Delivery::None)).unwrap();
+        scheduler.snooze("standup", 10).unwrap();
+        scheduler.skip_next("standup").unwrap();
+        let state = AppState { pending: scheduler.pending_fires(), ..AppState::default() };
+        StateStore::open(&path).unwrap().save(&state).unwrap();
+
+        // 再起動後に復元する（過ぎた予定は破棄される）
+        let mut saved = StateStore::open(&path).unwrap().state().pending.clone();
+        assert_eq!(saved, state.pending);
+        saved.snoozed.push(OneOffFire {
+            schedule_id: "standup".to_string(),
+            at: seconds_ago(Local::now(), STALE_PENDING_FIRE_SECONDS + 10),
+        });
+        let restarted = CronScheduler::new(audio_player());
+        restarted.restore_pending_fires(saved);
+        assert_eq!(restarted.pending_fires(), state.pending);
+    }
+
+    #[tokio::test]
+    async fn snoozed_fire_runs_once() {
+        let (mut scheduler, mut events) = start_scheduler(vec![schedule("standup", Delivery::None)]).await;
+
+        let at = scheduler.snooze("standup", 0).unwrap();
+        let event = next_event(&mut events).await;
+        assert_eq!(event.schedule_id, "standup");
+        assert_eq!(event.planned_at, at);
+        assert!(matches!(event.outcome, ScheduleOutcome::Fired), "{:?}", event.outcome);
+        assert!(scheduler.pending_fires().snoozed.is_empty());
+        scheduler.stop();
+    }
+
+    #[tokio::test]
+    async fn skipped_fire_is_reported_and_not_run() {
+        let mut standup = schedule("standup", Delivery::None);
+        let fire_at = Local::now() + chrono::Duration::seconds(2);
+        standup.cron = cron_at(fire_at);
+        let (mut scheduler, mut events) = start_scheduler(vec![standup]).await;
+
+        let at = scheduler.skip_next("standup").unwrap();
+        assert_eq!(at.timestamp(), fire_at.timestamp());
+        let event = next_event(&mut events).await;
+        assert_eq!(event.planned_at, at);
+        assert!(
+            matches!(&event.outcome, ScheduleOutcome::Skipped { reason } if reason == "skipped_by_user"),
+            "{:?}",
+            event.outcome
+        );
+        assert!(scheduler.pending_fires().skipped.is_empty());
+        scheduler.stop();
+    }
+
+    #[tokio::test]
+    async fn pause_suppresses_fires_until_it_ends() {
+        let (mut scheduler, mut events) = start_scheduler(vec![schedule("standup", Delivery::None)]).await;
+        let mut paused_until = scheduler.subscribe_paused_until();
+
+        let until = Local::now() + chrono::Duration::milliseconds(1500);
+        scheduler.pause_until(until);
+        assert_eq!(scheduler.paused_until(), Some(until));
+        scheduler.snooze("standup", 0).unwrap();
+        let event = next_event(&mut events).await;
+        assert!(
+            matches!(&event.outcome, ScheduleOutcome::Skipped { reason } if reason == "paused"),
+            "{:?}",
+            event.outcome
+        );
+
+        // 終了時刻に自動で再開する
+        tokio::time::timeout(Duration::from_secs(5), paused_until.wait_for(Option::is_none))
+            .await
+            .expect("pause did not end")
+            .unwrap();
+        assert!(Local::now() >= until);
+        assert_eq!(scheduler.paused_until(), None);
+
+        scheduler.snooze("standup", 0).unwrap();
+        let event = next_event(&mut events).await;
+        assert!(matches!(event.outcome, ScheduleOutcome::Fired), "{:?}", event.outcome);
+        scheduler.stop();
+    }
+
+    #[tokio::test]
+    async fn resume_ends_pause_immediately() {
+        let scheduler = CronScheduler::new(audio_player());
+        scheduler.pause_until(Local::now() + chrono::Duration::hours(1));
+        scheduler.resume();
+        assert_eq!(scheduler.paused_until(), None);
+    }
+
+    #[tokio::test]
+    async fn pause_is_restored_from_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        for (until, restored) in [
+            (Local::now() + chrono::Duration::hours(1), true),
+            (Local::now() - chrono::Duration::minutes(1), false),
+        ] {
+            StateStore::open(&path)
+                .unwrap()
+                .save(&AppState { paused_until: Some(until), ..AppState::default() })
+                .unwrap();
+
+            let scheduler = CronScheduler::new(audio_player());
+            scheduler.restore_paused_until(StateStore::open(&path).unwrap().state().paused_until);
+            assert_eq!(scheduler.paused_until(), restored.then_some(until));
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    mod desktop_notification {
+        use super::*;
+        use std::io::BufRead;
+        use zbus::zvariant::OwnedValue;
+
+        /// freedesktop 通知デーモンの代わり（受け取った通知のタイトルと本文を記録する）
+        struct NotificationDaemon {
+            received: Arc<Mutex<Vec<(String, String)>>>,
+        }
+
+        #[zbus::interface(name = "org.freedesktop.Notifications")]
+        impl NotificationDaemon {
+            #[allow(clippy::too_many_arguments)]
+            fn notify(
+                &self,
+                _app_name: String,
+                _replaces_id: u32,
+                _app_icon: String,
+                summary: String,
+                body: String,
+                _actions: Vec<String>,
+                _hints: HashMap<String, OwnedValue>,
+                _expire_timeout: i32,
+            ) -> u32 {
+                let mut received = self.received.lock().unwrap();
+                received.push((summary, body));
+                received.len() as u32
+            }
+        }
+
+        #[tokio::test]
+        #[ignore = "requires dbus-daemon"]
+        async fn delivers_by_delivery_mode() {
+            // 専用のセッションバスを起動して通知デーモンの代わりを登録する
+            let mut bus = std::process::Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .expect("failed to start dbus-daemon");
+            let mut address = String::new();
+            std::io::BufReader::new(bus.stdout.take().unwrap()).read_line(&mut address).unwrap();
+            let address = address.trim().to_string();
+            // SAFETY: このテストは単独で実行され（ignore）、通知の表示前に設定する
+            unsafe { std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &address) };
+
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let _daemon = zbus::connection::Builder::address(address.as_str())
+                .unwrap()
+                .name("org.freedesktop.Notifications")
+                .unwrap()
+                .serve_at("/org/freedesktop/Notifications", NotificationDaemon { received: received.clone() })
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+
+            let mut scheduler = CronScheduler::new(audio_player());
+            for (id, delivery) in [("notify", Delivery::Notify), ("sound", Delivery::Sound), ("both", Delivery::Both)] {
+                let mut schedule = schedule(id, delivery);
+                // 音声ファイルはないため、再生したかは組み込みの代替音声が使われたかで確認する
+                schedule.resolved_fallback = vec![FallbackSound::Builtin("beep".to_string())];
+                schedule.notification = Some(crate::config::NotificationTemplate {
+                    title: Some("Title {id}".to_string()),
+                    body: Some("Body {id}".to_string()),
+                });
+                scheduler.add_schedule(schedule).unwrap();
+            }
+            let mut events = scheduler.start(CancellationToken::new()).await.unwrap();
+
+            for (id, notified, played) in [("notify", true, false), ("sound", false, true), ("both", true, true)] {
+                received.lock().unwrap().clear();
+                scheduler.trigger(id).unwrap();
+                let event = wait_for_result(&mut events, id).await;
+
+                let expected_fallback = played.then(|| "builtin:beep".to_string());
+                assert!(
+                    matches!(&event.outcome, ScheduleOutcome::Succeeded { fallback } if *fallback == expected_fallback),
+                    "{}: {:?}",
+                    id,
+                    event.outcome
+                );
+                let expected_notifications = if notified {
+                    vec![(format!("Title {}", id), format!("Body {}", id))]
+                } else {
+                    Vec::new()
+                };
+                assert_eq!(*received.lock().unwrap(), expected_notifications, "{}", id);
+            }
+
+            scheduler.stop();
+            bus.kill().unwrap();
+            bus.wait().unwrap();
+        }
+    }
+}
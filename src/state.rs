@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::scheduler::PendingFires;
+
+/// 再起動後も保持するアプリケーションの状態
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppState {
+    /// スヌーズ・スキップ予定
+    #[serde(default)]
+    pub pending: PendingFires,
+    /// 一時停止の終了時刻
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused_until: Option<DateTime<Local>>,
+    /// ミュート中か
+    #[serde(default)]
+    pub muted: bool,
+}
+
+/// 状態ファイル（JSON形式）
+pub struct StateStore {
+    path: PathBuf,
+    saved: AppState,
+}
+
+impl StateStore {
+    /// 状態ファイルを読み込む（存在しない場合は空の状態）
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let saved = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read state file: {:?}", path))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse state file: {:?}", path))?
+        } else {
+            AppState::default()
+        };
+
+        Ok(Self { path, saved })
+    }
+
+    /// 最後に保存した状態
+    pub fn state(&self) -> &AppState {
+        &self.saved
+    }
+
+    /// 状態が変化していれば保存（一時ファイルに書き込んでから置き換える）
+    pub fn save(&mut self, state: &AppState) -> Result<()> {
+        if *state == self.saved {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create state directory: {:?}", parent))?;
+        }
+
+        let content = serde_json::to_string_pretty(state)
+            .context("Failed to serialize state")?;
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, content)
+            .with_context(|| format!("Failed to write state file: {:?}", temp_path))?;
+        std::fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to replace state file: {:?}", self.path))?;
+
+        self.saved = state.clone();
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+//! systemd への状態通知（`Type=notify` のユニット用）
+//!
+//! systemd 配下でない場合（`NOTIFY_SOCKET` が未設定）や Linux 以外では何もしない
+
+/// 起動完了を通知
+pub fn notify_ready(status: &str) {
+    #[cfg(target_os = "linux")]
+    send(&[sd_notify::NotifyState::Ready, sd_notify::NotifyState::Status(status)]);
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = status;
+}
+
+/// 設定の再読み込み開始を通知（完了後に `notify_ready` を呼ぶ）
+pub fn notify_reloading() {
+    #[cfg(target_os = "linux")]
+    match sd_notify::NotifyState::monotonic_usec_now() {
+        Ok(now) => send(&[sd_notify::NotifyState::Reloading, now]),
+        Err(e) => tracing::warn!("Failed to read monotonic clock for systemd notification: {}", e),
+    }
+}
+
+/// 終了処理の開始を通知
+pub fn notify_stopping() {
+    #[cfg(target_os = "linux")]
+    send(&[sd_notify::NotifyState::Stopping]);
+}
+
+#[cfg(target_os = "linux")]
+fn send(state: &[sd_notify::NotifyState]) {
+    if let Err(e) = sd_notify::notify(false, state) {
+        tracing::warn!("Failed to notify systemd: {}", e);
+    }
+}
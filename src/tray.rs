@@ -1,217 +1,581 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveTime};
 use tokio::sync::mpsc;
 use tray_icon::{
-    menu::{Menu, MenuItem, PredefinedMenuItem, MenuId},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, MenuId, Submenu},
     TrayIcon, TrayIconBuilder,
 };
 use tray_icon::menu::MenuEvent;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
+use crate::autostart::AutoStart;
+use crate::audio::SoundInfo;
+use crate::config::Schedule;
+use crate::i18n::{t, tf};
+use crate::scheduler::{CronScheduler, NextFire, PendingFires};
 
 pub struct SystemTray {
     tray_icon: TrayIcon,
-    menu_event_receiver: mpsc::UnboundedReceiver<TrayMenuEvent>,
+    // メインループに渡すまで保持するメニューイベントの受信側
+    menu_event_receiver: Option<mpsc::UnboundedReceiver<TrayMenuEvent>>,
+    // 読み込んだ設定ファイルのパス
+    config_path: PathBuf,
+    // プラットフォームごとの自動起動実装
+    autostart: Box<dyn AutoStart>,
+    // メニューに表示するスケジュール
+    schedules: Vec<Schedule>,
+    // スヌーズ・スキップ予定（メニューに表示）
+    pending: PendingFires,
+    // 音声ファイルの情報（ファイルパスごと、スケジュールのサブメニューに表示）
+    sound_info: HashMap<String, SoundInfo>,
+    // 一時停止メニューの再開時刻の候補
+    pause_times: Vec<NaiveTime>,
+    // メニューに表示している一時停止・ミュート状態と音量
+    paused_until: Option<DateTime<Local>>,
+    muted: bool,
+    volume: u8,
+    // メニューIDと対応するイベント（メニュー再構築時に更新）
+    menu_actions: Arc<Mutex<HashMap<MenuId, TrayMenuEvent>>>,
+    // 現在表示中のアイコン状態とツールチップ
+    icon_state: IconState,
+    tooltip: String,
     // 固定メニューID
     toggle_autostart_id: MenuId,
     open_config_id: MenuId,
+    reload_config_id: MenuId,
     open_logs_id: MenuId,
     exit_id: MenuId,
-    // シャットダウン用チャンネル
-    shutdown_tx: mpsc::UnboundedSender<()>,
+}
+
+/// 一時停止の選択肢（分）
+const PAUSE_MINUTES: [u32; 2] = [15, 60];
+
+/// 音量メニューの選択肢（%）
+const VOLUME_LEVELS: [u8; 5] = [0, 25, 50, 75, 100];
+
+/// スヌーズの選択肢（分）
+const SNOOZE_MINUTES: [u32; 3] = [5, 10, 15];
+
+/// トレイアイコンの表示状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconState {
+    Normal,
+    Muted,
+    QuietHours,
+    Error,
+    Paused,
+}
+
+/// トレイに表示するアプリケーションの状態
+#[derive(Debug, Clone, Default)]
+pub struct TrayStatus {
+    pub next_fire: Option<NextFire>,
+    /// 一時停止の終了時刻
+    pub paused_until: Option<DateTime<Local>>,
+    pub muted: bool,
+    /// 現在の全体の音量（0-100）
+    pub volume: u8,
+    pub quiet_hours: bool,
+    /// スケジュールIDごとのエラー（音声ファイルの欠落・再生失敗など）
+    pub errors: BTreeMap<String, String>,
+}
+
+impl TrayStatus {
+    /// 表示するアイコン状態（エラー > 一時停止 > ミュート > 静音時間帯 の優先順）
+    pub fn icon_state(&self) -> IconState {
+        if !self.errors.is_empty() {
+            IconState::Error
+        } else if self.paused_until.is_some() {
+            IconState::Paused
+        } else if self.muted {
+            IconState::Muted
+        } else if self.quiet_hours {
+            IconState::QuietHours
+        } else {
+            IconState::Normal
+        }
+    }
+
+    /// ツールチップ文字列（次回のチャイムとカウントダウン、状態）
+    pub fn tooltip(&self, now: &DateTime<Local>) -> String {
+        let mut lines = vec!["Tasktray Chime".to_string()];
+
+        match &self.next_fire {
+            Some(next) => lines.push(tf("tooltip.next", &[
+                ("id", &next.schedule_id),
+                ("time", &next.at.format("%H:%M:%S")),
+                ("countdown", &Self::format_countdown(next.at.signed_duration_since(*now))),
+            ])),
+            None => lines.push(t("tooltip.next_none")),
+        }
+
+        match self.icon_state() {
+            IconState::Error => {
+                let ids: Vec<&str> = self.errors.keys().map(String::as_str).collect();
+                lines.push(tf("tooltip.error", &[("ids", &ids.join(", "))]));
+            }
+            IconState::Paused => {
+                let until = self.paused_until.map(|t| t.format("%H:%M").to_string()).unwrap_or_default();
+                lines.push(tf("tooltip.paused", &[("time", &until)]));
+            }
+            IconState::Muted => lines.push(t("tooltip.muted")),
+            IconState::QuietHours => lines.push(t("tooltip.quiet_hours")),
+            IconState::Normal => {}
+        }
+
+        // Windowsのツールチップは128文字（終端を含む）まで
+        lines.join("\n").chars().take(127).collect()
+    }
+
+    /// ツールチップの表示が次に変わるまでの時間
+    ///
+    /// カウントダウンが変わるとき（1分未満は毎秒、それ以外は分が変わるとき）と、静音時間帯の切り替わりに備えた毎分0秒のうち早い方
+    pub fn next_refresh_in(&self, now: &DateTime<Local>) -> std::time::Duration {
+        const MINUTE_MS: i64 = 60_000;
+        let to_next_minute = MINUTE_MS - now.timestamp_millis().rem_euclid(MINUTE_MS);
+        let to_countdown_change = self.next_fire.as_ref().and_then(|next| {
+            let remaining = next.at.signed_duration_since(*now).num_milliseconds();
+            if remaining <= 0 {
+                return None;
+            }
+            let step = if remaining <= MINUTE_MS { 1_000 } else { MINUTE_MS };
+            Some(match remaining % step {
+                0 => step,
+                rest => rest,
+            })
+        });
+        let wait = to_countdown_change.map_or(to_next_minute, |wait| wait.min(to_next_minute));
+        // 境界をわずかに過ぎてから更新する
+        std::time::Duration::from_millis(wait as u64 + 10)
+    }
+
+    fn format_countdown(remaining: chrono::Duration) -> String {
+        let seconds = remaining.num_seconds().max(0);
+        if seconds < 60 {
+            tf("countdown.seconds", &[("seconds", &seconds)])
+        } else if seconds < 3600 {
+            tf("countdown.minutes", &[("minutes", &(seconds / 60))])
+        } else {
+            tf("countdown.hours", &[("hours", &(seconds / 3600)), ("minutes", &(seconds % 3600 / 60))])
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum TrayMenuEvent {
     ToggleAutoStart,
+    /// スケジュールの有効/無効を切り替え
+    ToggleSchedule(String),
+    /// スケジュールの音声を今すぐ再生
+    PlayScheduleNow(String),
+    /// 指定分数だけ全スケジュールを一時停止
+    PauseFor(u32),
+    /// 指定時刻（次に来るその時刻）まで全スケジュールを一時停止
+    PauseUntil(NaiveTime),
+    /// 一時停止を解除
+    Resume,
+    /// ミュートの切り替え
+    ToggleMute,
+    /// 再生中の音声を停止
+    StopPlayback,
+    /// 全体の音量を変更（このセッションのみ）
+    SetVolume(u8),
+    /// 現在の音量でテスト再生
+    TestVolume,
+    /// 現在の音量を設定ファイルに保存
+    SaveVolume,
+    /// 指定分数後にもう一度実行
+    SnoozeSchedule(String, u32),
+    /// 次回の実行をスキップ
+    SkipNextRun(String),
+    /// 次回のスキップを取り消す
+    CancelSkip(String),
     OpenConfig,
+    ReloadConfig,
     OpenLogsDir,
     Exit,
 }
 
-impl SystemTray {
-    pub fn new() -> Result<Self> {
-        // 固定IDを作成
-        let toggle_autostart_id = MenuId::new("toggle_autostart");
-        let open_config_id = MenuId::new("open_config");
-        let open_logs_id = MenuId::new("open_logs");
-        let exit_id = MenuId::new("exit");
-        
-        // 自動起動の現在の状態を確認
-        let autostart_enabled = Self::check_autostart_status();
-        let autostart_text = if autostart_enabled {
-            "自動起動を無効化 (現在: 有効)"
-        } else {
-            "自動起動を有効化 (現在: 無効)"
-        };
-        
-        // 固定IDを使用してメニューアイテムを作成
-        let toggle_autostart = MenuItem::with_id(toggle_autostart_id.clone(), autostart_text, true, None);
-        let separator1 = PredefinedMenuItem::separator();
-        let open_config = MenuItem::with_id(open_config_id.clone(), "設定ファイルを開く", true, None);
-        let open_logs = MenuItem::with_id(open_logs_id.clone(), "ログディレクトリを開く", true, None);
-        let separator2 = PredefinedMenuItem::separator();
-        let exit = MenuItem::with_id(exit_id.clone(), "終了", true, None);
+/// タスクトレイを表示できる環境か
+///
+/// Linux ではディスプレイ（X11 / Wayland）に接続できない場合に false を返す
+pub fn tray_host_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()))
+    }
 
-        // コンテキストメニューを構築
-        let menu = Menu::with_items(&[
-            &toggle_autostart,
-            &separator1,
-            &open_config,
-            &open_logs,
-            &separator2,
-            &exit,
-        ])
-        .context("Failed to create tray menu")?;
+    #[cfg(not(target_os = "linux"))]
+    {
+        true
+    }
+}
 
-        // トレイアイコンを作成
+impl SystemTray {
+    pub fn new(config_path: PathBuf, autostart: Box<dyn AutoStart>, schedules: &[Schedule]) -> Result<Self> {
+        // トレイアイコンを作成（メニューは後で構築）
         let tray_icon = TrayIconBuilder::new()
-            .with_menu(Box::new(menu))
-            .with_tooltip("Tasktray Chime - 時報アプリ")
-            .with_icon(Self::create_tray_icon())
+            .with_tooltip(t("tray.tooltip"))
+            .with_icon(Self::create_tray_icon(IconState::Normal))
             .build()
             .context("Failed to create tray icon")?;
 
-        // メニューイベント処理用のチャンネル
+        // メニューのクリックはイベントハンドラーから直接チャンネルに送る（ポーリングしない）
         let (event_tx, event_rx) = mpsc::unbounded_channel();
-        
-        // シャットダウン用チャンネル
-        let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
-
-        // メニューイベントリスナーを設定
-        let event_tx_clone = event_tx.clone();
-        let toggle_autostart_id_clone = toggle_autostart_id.clone();
-        let open_config_id_clone = open_config_id.clone();
-        let open_logs_id_clone = open_logs_id.clone();
-        let exit_id_clone = exit_id.clone();
-        
-        // メニューイベント処理用のタスクを起動
-        tokio::spawn(async move {
-            let menu_channel = MenuEvent::receiver();
-            
-            loop {
-                // シャットダウンシグナルをチェック
-                if shutdown_rx.try_recv().is_ok() {
-                    tracing::debug!("Menu event listener received shutdown signal");
-                    break;
-                }
-                
-                // ノンブロッキングでメニューイベントをチェック
-                match menu_channel.try_recv() {
-                    Ok(event) => {
-                        tracing::debug!("Received menu event: {:?}", event.id);
-                        
-                        let menu_event = if event.id == toggle_autostart_id_clone {
-                            TrayMenuEvent::ToggleAutoStart
-                        } else if event.id == open_config_id_clone {
-                            TrayMenuEvent::OpenConfig
-                        } else if event.id == open_logs_id_clone {
-                            TrayMenuEvent::OpenLogsDir
-                        } else if event.id == exit_id_clone {
-                            TrayMenuEvent::Exit
-                        } else {
-                            tracing::warn!("Unknown menu item clicked: {:?}", event.id);
-                            continue;
-                        };
-
-                        if let Err(_) = event_tx_clone.send(menu_event) {
-                            tracing::warn!("Failed to send tray menu event - channel closed");
-                            break;
-                        }
-                    }
-                    Err(_) => {
-                        // イベントがない場合は少し待機
-                        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        let menu_actions: Arc<Mutex<HashMap<MenuId, TrayMenuEvent>>> = Arc::new(Mutex::new(HashMap::new()));
+        let menu_actions_clone = menu_actions.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            tracing::debug!("Received menu event: {:?}", event.id);
+
+            let menu_event = menu_actions_clone
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&event.id)
+                .cloned();
+
+            match menu_event {
+                Some(menu_event) => {
+                    if event_tx.send(menu_event).is_err() {
+                        tracing::debug!("Tray menu event dropped - event loop has stopped");
                     }
                 }
+                None => tracing::warn!("Unknown menu item clicked: {:?}", event.id),
             }
-            tracing::debug!("Menu event listener task terminated");
-        });
+        }));
 
-        Ok(Self {
+        let mut tray = Self {
             tray_icon,
-            menu_event_receiver: event_rx,
-            toggle_autostart_id,
-            open_config_id,
-            open_logs_id,
-            exit_id,
-            shutdown_tx,
-        })
+            menu_event_receiver: Some(event_rx),
+            config_path,
+            autostart,
+            schedules: schedules.to_vec(),
+            pending: PendingFires::default(),
+            sound_info: HashMap::new(),
+            pause_times: Vec::new(),
+            paused_until: None,
+            muted: false,
+            volume: 0,
+            menu_actions,
+            icon_state: IconState::Normal,
+            tooltip: t("tray.tooltip"),
+            toggle_autostart_id: MenuId::new("toggle_autostart"),
+            open_config_id: MenuId::new("open_config"),
+            reload_config_id: MenuId::new("reload_config"),
+            open_logs_id: MenuId::new("open_logs"),
+            exit_id: MenuId::new("exit"),
+        };
+
+        tray.update_menu()?;
+        Ok(tray)
     }
 
-    /// メニューを現在の自動起動状態に基づいて更新
+    /// 表示するスケジュールを更新してメニューを再構築
+    pub fn set_schedules(&mut self, schedules: &[Schedule]) -> Result<()> {
+        self.schedules = schedules.to_vec();
+        self.update_menu()
+    }
+
+    /// スケジュールのサブメニューに表示する音声ファイルの情報を更新（変化があればメニューを再構築）
+    pub fn set_sound_info(&mut self, sound_info: HashMap<String, SoundInfo>) -> Result<()> {
+        if self.sound_info == sound_info {
+            return Ok(());
+        }
+        self.sound_info = sound_info;
+        self.update_menu()
+    }
+
+    /// 一時停止メニューに表示する再開時刻の候補を設定
+    pub fn set_pause_times(&mut self, pause_times: Vec<NaiveTime>) -> Result<()> {
+        self.pause_times = pause_times;
+        self.update_menu()
+    }
+
+    /// スヌーズ・スキップ予定を更新（変化があればメニューを再構築）
+    pub fn set_pending_fires(&mut self, pending: &PendingFires) -> Result<()> {
+        if self.pending == *pending {
+            return Ok(());
+        }
+        self.pending = pending.clone();
+        self.update_menu()
+    }
+
+    /// メニューを現在の自動起動状態とスケジュールに基づいて再構築
     pub fn update_menu(&mut self) -> Result<()> {
-        let autostart_enabled = Self::check_autostart_status();
+        let mut actions = HashMap::new();
+
+        let autostart_enabled = self.get_autostart_status();
         let autostart_text = if autostart_enabled {
-            "自動起動を無効化 (現在: 有効)"
+            t("menu.autostart_disable")
         } else {
-            "自動起動を有効化 (現在: 無効)"
+            t("menu.autostart_enable")
         };
         
-        // 固定IDを使用して新しいメニューを作成
+        // 固定IDを使用してメニューアイテムを作成
         let toggle_autostart = MenuItem::with_id(
             self.toggle_autostart_id.clone(), 
             autostart_text, 
             true, 
             None
         );
+        actions.insert(self.toggle_autostart_id.clone(), TrayMenuEvent::ToggleAutoStart);
+
+        let schedules_menu = self.build_schedules_menu(&mut actions)?;
+        let pause_item = self.build_pause_menu(&mut actions)?;
+        let mute_id = MenuId::new("toggle_mute");
+        actions.insert(mute_id.clone(), TrayMenuEvent::ToggleMute);
+        let mute = CheckMenuItem::with_id(mute_id, t("menu.mute"), true, self.muted, None);
+        let stop_id = MenuId::new("stop_playback");
+        actions.insert(stop_id.clone(), TrayMenuEvent::StopPlayback);
+        let stop_playback = MenuItem::with_id(stop_id, t("menu.stop_playback"), true, None);
+        let volume_menu = self.build_volume_menu(&mut actions)?;
+
         let separator1 = PredefinedMenuItem::separator();
         let open_config = MenuItem::with_id(
             self.open_config_id.clone(),
-            "設定ファイルを開く", 
+            t("menu.open_config"),
             true, 
             None
         );
+        actions.insert(self.open_config_id.clone(), TrayMenuEvent::OpenConfig);
+        let reload_config = MenuItem::with_id(
+            self.reload_config_id.clone(),
+            t("menu.reload_config"),
+            true, 
+            None
+        );
+        actions.insert(self.reload_config_id.clone(), TrayMenuEvent::ReloadConfig);
         let open_logs = MenuItem::with_id(
             self.open_logs_id.clone(),
-            "ログディレクトリを開く", 
+            t("menu.open_logs"),
             true, 
             None
         );
+        actions.insert(self.open_logs_id.clone(), TrayMenuEvent::OpenLogsDir);
         let separator2 = PredefinedMenuItem::separator();
         let exit = MenuItem::with_id(
             self.exit_id.clone(),
-            "終了", 
+            t("menu.exit"),
             true, 
             None
         );
+        actions.insert(self.exit_id.clone(), TrayMenuEvent::Exit);
 
         let menu = Menu::with_items(&[
+            &schedules_menu,
+            pause_item.as_ref(),
+            &mute,
+            &stop_playback,
+            &volume_menu,
             &toggle_autostart,
             &separator1,
             &open_config,
+            &reload_config,
             &open_logs,
             &separator2,
             &exit,
         ])
         .context("Failed to create updated tray menu")?;
 
-        // メニューを更新
+        // メニューIDの対応表を差し替えてからメニューを更新
+        *self.menu_actions.lock().unwrap_or_else(|e| e.into_inner()) = actions;
         self.tray_icon.set_menu(Some(Box::new(menu)));
         
-        tracing::debug!("Updated tray menu with autostart status: {}", autostart_enabled);
+        tracing::debug!(
+            "Updated tray menu with autostart status: {}, {} schedule(s)",
+            autostart_enabled,
+            self.schedules.len()
+        );
         Ok(())
     }
 
+    /// 「スケジュール」サブメニューを構築
+    ///
+    /// 各スケジュールに有効/無効のチェック、今すぐ再生、次回実行時刻、音声ファイルの情報を表示する
+    fn build_schedules_menu(&self, actions: &mut HashMap<MenuId, TrayMenuEvent>) -> Result<Submenu> {
+        let schedules_menu = Submenu::new(t("menu.schedules"), true);
+
+        if self.schedules.is_empty() {
+            schedules_menu
+                .append(&MenuItem::new(t("menu.no_schedules"), false, None))
+                .context("Failed to build schedules menu")?;
+            return Ok(schedules_menu);
+        }
+
+        let now = Local::now();
+        for schedule in &self.schedules {
+            let next_fire = if schedule.enabled {
+                CronScheduler::next_fire_time(schedule, &now)
+            } else {
+                None
+            };
+
+            let title = match next_fire {
+                Some(next) => format!("{} ({})", schedule.id, Self::format_fire_time(next, &now)),
+                None if schedule.enabled => schedule.id.clone(),
+                None => tf("menu.schedule_disabled", &[("id", &schedule.id)]),
+            };
+
+            let toggle_id = MenuId::new(format!("schedule_toggle:{}", schedule.id));
+            let play_id = MenuId::new(format!("schedule_play:{}", schedule.id));
+            actions.insert(toggle_id.clone(), TrayMenuEvent::ToggleSchedule(schedule.id.clone()));
+            actions.insert(play_id.clone(), TrayMenuEvent::PlayScheduleNow(schedule.id.clone()));
+
+            let toggle = CheckMenuItem::with_id(toggle_id, t("menu.schedule_enabled"), true, schedule.enabled, None);
+            let play_now = MenuItem::with_id(play_id, t("menu.play_now"), true, None);
+            let next_fire_label = match next_fire {
+                Some(next) if self.pending.skipped.get(&schedule.id) == Some(&next) => {
+                    tf("menu.next_run_skipped", &[("time", &next.format("%Y-%m-%d %H:%M:%S"))])
+                }
+                Some(next) => tf("menu.next_run", &[("time", &next.format("%Y-%m-%d %H:%M:%S"))]),
+                None => t("menu.next_run_none"),
+            };
+            let next_fire_item = MenuItem::new(next_fire_label, false, None);
+
+            let items: [&dyn IsMenuItem; 3] = [&toggle, &play_now, &next_fire_item];
+            let schedule_menu = Submenu::with_items(title, true, &items)
+                .with_context(|| format!("Failed to build menu for schedule '{}'", schedule.id))?;
+            if schedule.delivery.plays_sound()
+                && let Some(info) = self.sound_info.get(schedule.sound_path().to_string_lossy().as_ref())
+            {
+                let label = tf("menu.sound_info", &[("info", info)]);
+                schedule_menu
+                    .append(&MenuItem::new(label, false, None))
+                    .with_context(|| format!("Failed to build menu for schedule '{}'", schedule.id))?;
+            }
+            if schedule.reminder {
+                self.append_reminder_items(&schedule_menu, schedule, actions, &now)
+                    .with_context(|| format!("Failed to build menu for schedule '{}'", schedule.id))?;
+            }
+            schedules_menu
+                .append(&schedule_menu)
+                .context("Failed to build schedules menu")?;
+        }
+
+        Ok(schedules_menu)
+    }
+
+    /// リマインダー用のスヌーズ・スキップ項目を追加
+    fn append_reminder_items(
+        &self,
+        schedule_menu: &Submenu,
+        schedule: &Schedule,
+        actions: &mut HashMap<MenuId, TrayMenuEvent>,
+        now: &DateTime<Local>,
+    ) -> Result<()> {
+        schedule_menu.append(&PredefinedMenuItem::separator())?;
+
+        let snooze_menu = Submenu::new(t("menu.snooze"), true);
+        for minutes in SNOOZE_MINUTES {
+            let id = MenuId::new(format!("schedule_snooze:{}:{}", minutes, schedule.id));
+            actions.insert(id.clone(), TrayMenuEvent::SnoozeSchedule(schedule.id.clone(), minutes));
+            snooze_menu.append(&MenuItem::with_id(id, tf("menu.snooze_minutes", &[("minutes", &minutes)]), true, None))?;
+        }
+        schedule_menu.append(&snooze_menu)?;
+
+        if let Some(skipped_at) = self.pending.skipped.get(&schedule.id) {
+            let id = MenuId::new(format!("schedule_cancel_skip:{}", schedule.id));
+            actions.insert(id.clone(), TrayMenuEvent::CancelSkip(schedule.id.clone()));
+            let label = tf("menu.cancel_skip", &[("time", &Self::format_fire_time(*skipped_at, now))]);
+            schedule_menu.append(&MenuItem::with_id(id, label, true, None))?;
+        } else {
+            let id = MenuId::new(format!("schedule_skip:{}", schedule.id));
+            actions.insert(id.clone(), TrayMenuEvent::SkipNextRun(schedule.id.clone()));
+            schedule_menu.append(&MenuItem::with_id(id, t("menu.skip_next"), schedule.enabled, None))?;
+        }
+
+        for fire in self.pending.snoozed.iter().filter(|f| f.schedule_id == schedule.id) {
+            let label = tf("menu.snoozed_until", &[("time", &Self::format_fire_time(fire.at, now))]);
+            schedule_menu.append(&MenuItem::new(label, false, None))?;
+        }
+
+        Ok(())
+    }
+
+    /// 音量メニューを作成（現在の音量にチェック）
+    fn build_volume_menu(&self, actions: &mut HashMap<MenuId, TrayMenuEvent>) -> Result<Submenu> {
+        let volume_menu = Submenu::new(tf("menu.volume", &[("volume", &self.volume)]), true);
+        for volume in VOLUME_LEVELS {
+            let id = MenuId::new(format!("volume:{}", volume));
+            actions.insert(id.clone(), TrayMenuEvent::SetVolume(volume));
+            volume_menu
+                .append(&CheckMenuItem::with_id(id, format!("{}%", volume), true, self.volume == volume, None))
+                .context("Failed to build volume menu")?;
+        }
+
+        let test_id = MenuId::new("volume_test");
+        let save_id = MenuId::new("volume_save");
+        actions.insert(test_id.clone(), TrayMenuEvent::TestVolume);
+        actions.insert(save_id.clone(), TrayMenuEvent::SaveVolume);
+        volume_menu
+            .append_items(&[
+                &PredefinedMenuItem::separator(),
+                &MenuItem::with_id(test_id, t("menu.volume_test"), true, None),
+                &MenuItem::with_id(save_id, t("menu.volume_save"), true, None),
+            ])
+            .context("Failed to build volume menu")?;
+        Ok(volume_menu)
+    }
+
+    /// 一時停止メニューを作成（一時停止中は再開項目）
+    fn build_pause_menu(&self, actions: &mut HashMap<MenuId, TrayMenuEvent>) -> Result<Box<dyn IsMenuItem>> {
+        if let Some(until) = self.paused_until {
+            let id = MenuId::new("resume");
+            actions.insert(id.clone(), TrayMenuEvent::Resume);
+            let label = tf("menu.resume", &[("time", &Self::format_fire_time(until, &Local::now()))]);
+            return Ok(Box::new(MenuItem::with_id(id, label, true, None)));
+        }
+
+        let pause_menu = Submenu::new(t("menu.pause"), true);
+        for minutes in PAUSE_MINUTES {
+            let id = MenuId::new(format!("pause_for:{}", minutes));
+            let label = if minutes % 60 == 0 {
+                tf("menu.pause_hours", &[("hours", &(minutes / 60))])
+            } else {
+                tf("menu.pause_minutes", &[("minutes", &minutes)])
+            };
+            actions.insert(id.clone(), TrayMenuEvent::PauseFor(minutes));
+            pause_menu
+                .append(&MenuItem::with_id(id, label, true, None))
+                .context("Failed to build pause menu")?;
+        }
+        for time in &self.pause_times {
+            let id = MenuId::new(format!("pause_until:{}", time.format("%H:%M")));
+            actions.insert(id.clone(), TrayMenuEvent::PauseUntil(*time));
+            pause_menu
+                .append(&MenuItem::with_id(id, tf("menu.pause_until", &[("time", &time.format("%H:%M"))]), true, None))
+                .context("Failed to build pause menu")?;
+        }
+        Ok(Box::new(pause_menu))
+    }
 
-    /// メニューイベントをタイムアウト付きで受信
-    pub async fn recv_menu_event_with_timeout(&mut self, timeout_ms: u64) -> Option<TrayMenuEvent> {
-        if let Ok(event) = tokio::time::timeout(
-            tokio::time::Duration::from_millis(timeout_ms),
-            self.menu_event_receiver.recv()
-        ).await {
-            return event;
+    /// 次回実行時刻を短く表示（当日なら時刻のみ）
+    fn format_fire_time(time: DateTime<Local>, now: &DateTime<Local>) -> String {
+        if time.date_naive() == now.date_naive() {
+            time.format("%H:%M:%S").to_string()
+        } else {
+            time.format("%m/%d %H:%M").to_string()
         }
-        None
     }
-    
-    /// システムトレイのシャットダウン処理
+
+    /// メニューイベントの受信側を取り出す（メインループで待ち受ける。2回目以降は None）
+    pub fn take_menu_events(&mut self) -> Option<mpsc::UnboundedReceiver<TrayMenuEvent>> {
+        self.menu_event_receiver.take()
+    }
+
+    /// システムトレイのシャットダウン処理（アイコンを非表示にする）
     pub fn shutdown(&self) {
-        // メニューイベントリスナータスクにシャットダウンシグナルを送信
-        let _ = self.shutdown_tx.send(());
-        tracing::debug!("Sent shutdown signal to menu event listener");
+        if let Err(e) = self.tray_icon.set_visible(false) {
+            tracing::warn!("Failed to hide tray icon: {}", e);
+        }
+        tracing::debug!("System tray shut down");
     }
 
-    /// タスクトレイアイコンを作成（組み込みアイコンファイル使用）
-    fn create_tray_icon() -> tray_icon::Icon {
+    /// タスクトレイアイコンを作成（組み込みアイコンファイルに状態を重ねて描画）
+    fn create_tray_icon(state: IconState) -> tray_icon::Icon {
         // 組み込みアイコンデータを使用
         let icon_bytes = include_bytes!("../resources/icons/chime-icon-32.ico");
         
@@ -220,7 +584,8 @@ impl SystemTray {
             Ok(img) => {
                 let rgba_img = img.to_rgba8();
                 let (width, height) = rgba_img.dimensions();
-                let rgba_data = rgba_img.into_raw();
+                let mut rgba_data = rgba_img.into_raw();
+                Self::apply_state_overlay(&mut rgba_data, width, height, state);
                 
                 match tray_icon::Icon::from_rgba(rgba_data, width, height) {
                     Ok(icon) => {
@@ -238,11 +603,11 @@ impl SystemTray {
         }
 
         // フォールバック: デフォルトアイコンを作成
-        Self::create_fallback_icon()
+        Self::create_fallback_icon(state)
     }
 
     /// フォールバックアイコンを作成（シンプルな鐘のような形状）
-    fn create_fallback_icon() -> tray_icon::Icon {
+    fn create_fallback_icon(state: IconState) -> tray_icon::Icon {
         // 16x16のアイコンデータ（RGBA形式）
         // より明確な鐘の形状を作成
         let mut icon_data = vec![0u8; 1024]; // 16x16 * 4 (RGBA)
@@ -283,6 +648,8 @@ impl SystemTray {
             }
         }
 
+        Self::apply_state_overlay(&mut icon_data, 16, 16, state);
+
         tray_icon::Icon::from_rgba(icon_data, 16, 16)
             .unwrap_or_else(|e| {
                 tracing::warn!("Failed to create icon from data: {}. Creating fallback.", e);
@@ -301,197 +668,230 @@ impl SystemTray {
             })
     }
 
-    /// 自動起動の状態を設定
-    pub fn set_autostart_status(&mut self, enabled: bool) -> Result<()> {
-        let result = {
-            #[cfg(target_os = "windows")]
-            {
-                self.set_windows_autostart_status(enabled)
-            }
-            
-            #[cfg(not(target_os = "windows"))]
-            {
-                let _ = enabled; // 未使用変数警告を回避
-                tracing::info!("Autostart setting not implemented for this platform");
-                Ok(())
-            }
+    /// 状態に応じてアイコンを加工（彩度・透明度の変更と右下のバッジ）
+    fn apply_state_overlay(rgba: &mut [u8], width: u32, height: u32, state: IconState) {
+        let (badge_color, desaturate, dim) = match state {
+            IconState::Normal => return,
+            IconState::Muted => ([90, 90, 90], true, false),       // 灰色
+            IconState::QuietHours => ([70, 130, 230], false, true), // 青
+            IconState::Error => ([220, 40, 40], false, false),      // 赤
+            IconState::Paused => ([240, 170, 0], true, false),      // 橙
         };
 
-        // 状態変更後にメニューを更新
-        if result.is_ok() {
-            if let Err(e) = self.update_menu() {
-                tracing::warn!("Failed to update menu after autostart status change: {}", e);
+        for pixel in rgba.chunks_exact_mut(4) {
+            if desaturate {
+                let gray = (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) as u8;
+                pixel[0] = gray;
+                pixel[1] = gray;
+                pixel[2] = gray;
+            }
+            if dim {
+                pixel[3] = (pixel[3] as u16 * 3 / 5) as u8;
             }
         }
 
-        result
+        // 右下に白い縁取り付きの丸いバッジを描画
+        let radius = width.min(height) as f32 * 0.22;
+        let center_x = width as f32 - radius - 0.5;
+        let center_y = height as f32 - radius - 0.5;
+        for y in 0..height {
+            for x in 0..width {
+                let dx = x as f32 + 0.5 - center_x;
+                let dy = y as f32 + 0.5 - center_y;
+                let distance = (dx * dx + dy * dy).sqrt();
+
+                let color = if distance <= radius {
+                    badge_color
+                } else if distance <= radius + 1.0 {
+                    [255, 255, 255]
+                } else {
+                    continue;
+                };
+
+                let idx = ((y * width + x) * 4) as usize;
+                rgba[idx..idx + 3].copy_from_slice(&color);
+                rgba[idx + 3] = 255;
+            }
+        }
     }
 
-    #[cfg(target_os = "windows")]
-    fn set_windows_autostart_status(&self, enabled: bool) -> Result<()> {
-        let exe_path = std::env::current_exe()
-            .context("Failed to get current executable path")?;
-        
-        // スタートアップフォルダのパスを取得
-        let startup_folder = Self::get_startup_folder_path()
-            .context("Failed to get startup folder path")?;
-        
-        let shortcut_path = startup_folder.join("TasktrayChime.lnk");
-        
-        if enabled {
-            // ショートカットを作成
-            Self::create_shortcut(&exe_path, &shortcut_path)
-                .context("Failed to create shortcut in startup folder")?;
-            
-            tracing::info!("Autostart enabled - created shortcut at: {:?}", shortcut_path);
-        } else {
-            // ショートカットを削除
-            if shortcut_path.exists() {
-                std::fs::remove_file(&shortcut_path)
-                    .context("Failed to remove shortcut from startup folder")?;
-                tracing::info!("Autostart disabled - removed shortcut");
-            } else {
-                tracing::info!("Autostart was not enabled - no shortcut found");
+    /// アプリケーションの状態をアイコンとツールチップに反映（変化がなければ何もしない）
+    pub fn set_status(&mut self, status: &TrayStatus) {
+        if status.paused_until != self.paused_until || status.muted != self.muted || status.volume != self.volume {
+            self.paused_until = status.paused_until;
+            self.muted = status.muted;
+            self.volume = status.volume;
+            if let Err(e) = self.update_menu() {
+                tracing::warn!("Failed to update tray menu: {}", e);
             }
         }
 
-        Ok(())
-    }
-    
-    #[cfg(target_os = "windows")]
-    fn get_startup_folder_path() -> Result<std::path::PathBuf> {
-        use directories::BaseDirs;
-        
-        // directories クレートを使用してスタートアップフォルダのパスを取得
-        let base_dirs = BaseDirs::new()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get base directories"))?;
-        
-        let startup_path = base_dirs.data_local_dir()
-            .join("Microsoft")
-            .join("Windows")
-            .join("Start Menu")
-            .join("Programs")
-            .join("Startup");
-        
-        // スタートアップフォルダが存在しない場合は作成
-        if !startup_path.exists() {
-            std::fs::create_dir_all(&startup_path)
-                .context("Failed to create startup folder")?;
+        let state = status.icon_state();
+        if state != self.icon_state {
+            match self.tray_icon.set_icon(Some(Self::create_tray_icon(state))) {
+                Ok(()) => {
+                    tracing::debug!("Tray icon state changed: {:?} -> {:?}", self.icon_state, state);
+                    self.icon_state = state;
+                }
+                Err(e) => tracing::warn!("Failed to update tray icon: {}", e),
+            }
         }
-        
-        Ok(startup_path)
-    }
-    
-    #[cfg(target_os = "windows")]
-    fn create_shortcut(target_path: &std::path::Path, shortcut_path: &std::path::Path) -> Result<()> {
-        use std::process::Command;
-        
-        // PowerShellを使ってショートカットを作成
-        let powershell_script = format!(
-            r#"
-            $WshShell = New-Object -ComObject WScript.Shell
-            $Shortcut = $WshShell.CreateShortcut("{}")
-            $Shortcut.TargetPath = "{}"
-            $Shortcut.WorkingDirectory = "{}"
-            $Shortcut.Description = "Tasktray Chime - 時報アプリ"
-            $Shortcut.Save()
-            "#,
-            shortcut_path.to_string_lossy().replace('\\', "\\\\"),
-            target_path.to_string_lossy().replace('\\', "\\\\"),
-            target_path.parent()
-                .unwrap_or_else(|| std::path::Path::new(""))
-                .to_string_lossy()
-                .replace('\\', "\\\\")
-        );
-        
-        let output = Command::new("powershell")
-            .args(&["-Command", &powershell_script])
-            .output()
-            .context("Failed to execute PowerShell command for creating shortcut")?;
-        
-        if !output.status.success() {
-            return Err(anyhow::anyhow!(
-                "Failed to create shortcut: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
+
+        let tooltip = status.tooltip(&Local::now());
+        if tooltip != self.tooltip {
+            match self.tray_icon.set_tooltip(Some(&tooltip)) {
+                Ok(()) => self.tooltip = tooltip,
+                Err(e) => tracing::warn!("Failed to update tray tooltip: {}", e),
+            }
         }
-        
-        Ok(())
     }
 
-    /// 自動起動の現在状態を取得（静的メソッド）
-    fn check_autostart_status() -> bool {
-        #[cfg(target_os = "windows")]
-        {
-            Self::check_windows_autostart_status().unwrap_or(false)
-        }
-        
-        #[cfg(not(target_os = "windows"))]
-        {
-            false
+    /// 自動起動の状態を設定
+    pub fn set_autostart_status(&mut self, enabled: bool) -> Result<()> {
+        let result = self.autostart.set_enabled(enabled);
+
+        // 状態変更後にメニューを更新
+        if result.is_ok() {
+            if let Err(e) = self.update_menu() {
+                tracing::warn!("Failed to update menu after autostart status change: {}", e);
+            }
         }
+
+        result
     }
 
     /// 自動起動の現在状態を取得
     pub fn get_autostart_status(&self) -> bool {
-        Self::check_autostart_status()
+        Self::check_autostart_status(self.autostart.as_ref())
     }
 
-    #[cfg(target_os = "windows")]
-    fn check_windows_autostart_status() -> Result<bool> {
-        // スタートアップフォルダのショートカットの存在を確認
-        let startup_folder = Self::get_startup_folder_path()
-            .context("Failed to get startup folder path")?;
-        
-        let shortcut_path = startup_folder.join("TasktrayChime.lnk");
-        Ok(shortcut_path.exists())
+    fn check_autostart_status(autostart: &dyn AutoStart) -> bool {
+        autostart.is_enabled().unwrap_or_else(|e| {
+            tracing::warn!("Failed to check autostart status: {}", e);
+            false
+        })
+    }
+
+    /// エディタの指定をプログラムと引数に分ける
+    ///
+    /// 指定全体が既存のファイルならそのまま使う（空白を含むパス）。
+    /// それ以外は Windows では `C:\tools\vim.exe -p` や `"C:\Program Files\app.exe" -n`、
+    /// その他のOSでは `code --wait` や `emacsclient -a '' -c` のように分割する
+    fn split_editor_command(editor: &str) -> Vec<String> {
+        if Path::new(editor).is_file() {
+            return vec![editor.to_string()];
+        }
+        if cfg!(target_os = "windows") {
+            return Self::split_windows_command(editor);
+        }
+        shlex::split(editor)
+            .filter(|parts| !parts.is_empty())
+            .unwrap_or_else(|| editor.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Windows のコマンドラインを空白で分割する
+    ///
+    /// `"` で囲んだ部分は空白を含めて1つの引数とする。パスの `\` はエスケープとして扱わない
+    fn split_windows_command(command: &str) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut current = String::new();
+        let mut in_part = false;
+        let mut in_quotes = false;
+        for c in command.chars() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    in_part = true;
+                }
+                c if c.is_whitespace() && !in_quotes => {
+                    if in_part {
+                        parts.push(std::mem::take(&mut current));
+                        in_part = false;
+                    }
+                }
+                c => {
+                    current.push(c);
+                    in_part = true;
+                }
+            }
+        }
+        if in_part {
+            parts.push(current);
+        }
+        parts
     }
 
     /// 設定ファイルを開く
-    pub fn open_config_file() -> Result<()> {
-        let config_path = Path::new("config.yaml");
-        
+    ///
+    /// エディタは `ui.editor` → `$VISUAL` → `$EDITOR` → OS標準の順に決定する。
+    /// エディタの終了を待てる場合はそのプロセスを返す（終了後に設定を再読み込みするため）
+    pub fn open_config_file(&self, editor: Option<&str>) -> Result<Option<Child>> {
         // 絶対パスに変換
-        let absolute_path = config_path.canonicalize()
+        let absolute_path = self.config_path.canonicalize()
             .or_else(|_| {
                 // canonicalize が失敗した場合（まだ存在しないファイル等）、手動で絶対パス化
                 std::env::current_dir()
-                    .map(|cwd| cwd.join(config_path))
+                    .map(|cwd| cwd.join(&self.config_path))
                     .context("Failed to get current directory")
             })?;
-        
+
+        let editor = editor
+            .map(str::to_string)
+            .or_else(|| std::env::var("VISUAL").ok())
+            .or_else(|| std::env::var("EDITOR").ok())
+            .filter(|e| !e.trim().is_empty());
+
+        if let Some(editor) = editor {
+            let mut parts = Self::split_editor_command(&editor).into_iter();
+            let program = parts.next().unwrap_or_default();
+            let child = Command::new(program)
+                .args(parts)
+                .arg(&absolute_path)
+                .spawn()
+                .with_context(|| format!("Failed to open config file with editor: {}", editor))?;
+
+            tracing::info!("Opened config file with editor '{}': {:?}", editor, absolute_path);
+            return Ok(Some(child));
+        }
+
         #[cfg(target_os = "windows")]
-        {
-            // Windows: notepad を直接実行
-            std::process::Command::new("notepad.exe")
+        let child = {
+            // Windows: notepad を直接実行（ウィンドウを閉じるまで終了しない）
+            let child = Command::new("notepad.exe")
                 .arg(&absolute_path)
                 .spawn()
                 .context("Failed to open config file with notepad.exe")?;
-        }
+            Some(child)
+        };
         
         #[cfg(target_os = "linux")]
-        {
-            std::process::Command::new("xdg-open")
+        let child = {
+            // xdg-open はすぐに終了するため、エディタの終了は待てない
+            Command::new("xdg-open")
                 .arg(&absolute_path)
                 .spawn()
                 .context("Failed to open config file")?;
-        }
+            None
+        };
         
         #[cfg(target_os = "macos")]
-        {
-            std::process::Command::new("open")
+        let child = {
+            Command::new("open")
                 .arg(&absolute_path)
                 .spawn()
                 .context("Failed to open config file")?;
-        }
+            None
+        };
+
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+        let child = None;
 
         tracing::info!("Opened config file: {:?}", absolute_path);
-        Ok(())
+        Ok(child)
     }
 
     /// ログディレクトリを開く
-    pub fn open_logs_directory(logs_dir: &str) -> Result<()> {
-        let logs_path = Path::new(logs_dir);
+    pub fn open_logs_directory(logs_path: &Path) -> Result<()> {
         
         // ディレクトリが存在しない場合は作成
         if !logs_path.exists() {
@@ -536,4 +936,40 @@ impl SystemTray {
         tracing::info!("Opened logs directory: {:?}", absolute_path);
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn split_editor_command_uses_shell_rules() {
+        assert_eq!(SystemTray::split_editor_command("code --wait"), ["code", "--wait"]);
+        assert_eq!(SystemTray::split_editor_command("emacsclient -a '' -c"), ["emacsclient", "-a", "", "-c"]);
+        assert_eq!(
+            SystemTray::split_editor_command(r#"'/opt/My Editor/edit' --new-window"#),
+            ["/opt/My Editor/edit", "--new-window"]
+        );
+    }
+
+    #[test]
+    fn split_windows_command_keeps_backslashes() {
+        assert_eq!(SystemTray::split_windows_command(r"C:\tools\vim.exe -p"), [r"C:\tools\vim.exe", "-p"]);
+        assert_eq!(
+            SystemTray::split_windows_command(r#""C:\Program Files\Notepad++\notepad++.exe" -multiInst"#),
+            [r"C:\Program Files\Notepad++\notepad++.exe", "-multiInst"]
+        );
+        assert_eq!(SystemTray::split_windows_command(r#"notepad.exe  "" -a"#), ["notepad.exe", "", "-a"]);
+        assert_eq!(SystemTray::split_windows_command("  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn split_editor_command_keeps_existing_path_with_spaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = dir.path().join("my editor");
+        std::fs::write(&editor, "").unwrap();
+        let editor = editor.to_string_lossy().to_string();
+        assert_eq!(SystemTray::split_editor_command(&editor), [editor]);
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 変更を検知してから通知するまでの待ち時間（書き込み中の連続したイベントをまとめる）
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+/// 音声ファイルの変更・削除を監視
+///
+/// ファイルを置き換えると監視が外れる環境があるため、ファイルのあるディレクトリを監視して対象のファイルのみ通知する
+pub struct SoundWatcher {
+    watcher: RecommendedWatcher,
+    /// 監視対象のファイル（絶対パス → 事前ロードに使うパス）
+    files: Arc<Mutex<HashMap<PathBuf, PathBuf>>>,
+    /// 監視中のディレクトリ
+    dirs: HashSet<PathBuf>,
+}
+
+impl SoundWatcher {
+    /// 監視を開始し、変更された音声ファイル（`set_files` で渡したパス）の受信側を返す
+    pub fn new() -> Result<(Self, mpsc::UnboundedReceiver<Vec<PathBuf>>)> {
+        let files: Arc<Mutex<HashMap<PathBuf, PathBuf>>> = Arc::new(Mutex::new(HashMap::new()));
+        let (raw_tx, raw_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let (changes_tx, changes_rx) = mpsc::unbounded_channel();
+
+        let watched = files.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let event = match result {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("Sound file watcher error: {}", e);
+                    return;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                return;
+            }
+            let watched = watched.lock().unwrap_or_else(|e| e.into_inner());
+            for path in &event.paths {
+                if let Some(file) = watched.get(path) {
+                    let _ = raw_tx.send(file.clone());
+                }
+            }
+        })
+        .context("Failed to create sound file watcher")?;
+
+        tokio::spawn(debounce(raw_rx, changes_tx));
+
+        Ok((
+            Self {
+                watcher,
+                files,
+                dirs: HashSet::new(),
+            },
+            changes_rx,
+        ))
+    }
+
+    /// 監視するファイルを差し替え（不要になったディレクトリの監視は解除する）
+    pub fn set_files<'a>(&mut self, files: impl IntoIterator<Item = &'a Path>) {
+        let mut watched = HashMap::new();
+        for file in files {
+            match std::path::absolute(file) {
+                Ok(path) => {
+                    watched.insert(path, file.to_path_buf());
+                }
+                Err(e) => tracing::warn!("Failed to resolve audio file path {:?}: {}", file, e),
+            }
+        }
+
+        let dirs: HashSet<PathBuf> = watched.keys().filter_map(|path| path.parent().map(Path::to_path_buf)).collect();
+        for dir in self.dirs.difference(&dirs) {
+            if let Err(e) = self.watcher.unwatch(dir) {
+                tracing::debug!("Failed to stop watching {:?}: {}", dir, e);
+            }
+        }
+        let mut watching = HashSet::new();
+        for dir in dirs {
+            if self.dirs.contains(&dir) {
+                watching.insert(dir);
+                continue;
+            }
+            match self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    tracing::debug!("Watching audio directory: {:?}", dir);
+                    watching.insert(dir);
+                }
+                Err(e) => tracing::warn!("Failed to watch audio directory {:?}, changes will not be detected: {}", dir, e),
+            }
+        }
+
+        tracing::info!("Watching {} audio file(s) in {} directory(ies)", watched.len(), watching.len());
+        *self.files.lock().unwrap_or_else(|e| e.into_inner()) = watched;
+        self.dirs = watching;
+    }
+}
+
+/// 短時間に続いた変更をまとめて送信（監視が終了すると終わる）
+async fn debounce(mut raw_rx: mpsc::UnboundedReceiver<PathBuf>, changes_tx: mpsc::UnboundedSender<Vec<PathBuf>>) {
+    while let Some(file) = raw_rx.recv().await {
+        let mut changed = BTreeSet::from([file]);
+        let deadline = tokio::time::Instant::now() + DEBOUNCE_DELAY;
+        while let Ok(Some(file)) = tokio::time::timeout_at(deadline, raw_rx.recv()).await {
+            changed.insert(file);
+        }
+        if changes_tx.send(changed.into_iter().collect()).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+use crate::config::WebhookConfig;
+use crate::scheduler::ScheduleEvent;
+
+/// 再送の間隔（初回。再送のたびに2倍にする）
+const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// スケジュールイベントを Webhook に送信
+pub struct WebhookPublisher {
+    client: reqwest::Client,
+    hooks: Vec<WebhookConfig>,
+}
+
+impl WebhookPublisher {
+    pub fn new(hooks: &[WebhookConfig]) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("tasktray-chime/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Failed to create HTTP client for webhooks")?;
+        Ok(Self {
+            client,
+            hooks: hooks.to_vec(),
+        })
+    }
+
+    /// 送信先を更新（設定の再読み込み時）
+    pub fn set_hooks(&mut self, hooks: &[WebhookConfig]) {
+        self.hooks = hooks.to_vec();
+    }
+
+    /// イベントを対象の送信先すべてにバックグラウンドで送信
+    pub fn publish(&self, event: &ScheduleEvent) {
+        let kind = event.outcome.kind();
+        for hook in self.hooks.iter().filter(|hook| hook.accepts(kind)) {
+            let client = self.client.clone();
+            let hook = hook.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                if let Err(e) = deliver(&client, &hook, &event, RETRY_INITIAL_DELAY).await {
+                    tracing::error!("Failed to deliver '{}' event for schedule '{}' to webhook {}: {:#}", kind, event.schedule_id, hook.url, e);
+                }
+            });
+        }
+    }
+}
+
+/// 1件のイベントを送信（失敗時は `retries` 回まで `retry_delay` から倍々の間隔を空けて再送）
+async fn deliver(client: &reqwest::Client, hook: &WebhookConfig, event: &ScheduleEvent, retry_delay: Duration) -> Result<()> {
+    let mut delay = retry_delay;
+    let mut attempt = 0;
+    loop {
+        match send(client, hook, event).await {
+            Ok(()) => {
+                tracing::debug!("Delivered '{}' event for schedule '{}' to webhook {}", event.outcome.kind(), event.schedule_id, hook.url);
+                return Ok(());
+            }
+            Err(e) if attempt < hook.retries => {
+                attempt += 1;
+                tracing::warn!("Webhook {} failed, retrying in {:?} ({}/{}): {:#}", hook.url, delay, attempt, hook.retries, e);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+async fn send(client: &reqwest::Client, hook: &WebhookConfig, event: &ScheduleEvent) -> Result<()> {
+    let mut request = client
+        .post(&hook.url)
+        .timeout(Duration::from_secs(hook.timeout_seconds))
+        .json(event);
+    for (name, value) in &hook.headers {
+        request = request.header(name, value);
+    }
+
+    let response = request.send().await.context("Request failed")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Unexpected response status {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use chrono::Local;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use crate::scheduler::ScheduleOutcome;
+
+    /// テスト用の受信側（最初の `failures` 回は 500 を返し、`delay` だけ待ってから応答する）
+    #[derive(Clone, Default)]
+    struct StandIn {
+        failures: usize,
+        delay: Duration,
+        requests: Arc<AtomicUsize>,
+        received: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    async fn receive(State(stand_in): State<StandIn>, Json(body): Json<serde_json::Value>) -> StatusCode {
+        let count = stand_in.requests.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(stand_in.delay).await;
+        stand_in.received.lock().unwrap().push(body);
+        if count < stand_in.failures { StatusCode::INTERNAL_SERVER_ERROR } else { StatusCode::OK }
+    }
+
+    /// 受信側を空きポートで起動し、送信先の URL を返す
+    async fn spawn_stand_in(stand_in: StandIn) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/hook", post(receive)).with_state(stand_in);
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}/hook", addr)
+    }
+
+    fn hook(url: String, retries: u32) -> WebhookConfig {
+        WebhookConfig {
+            url,
+            events: Vec::new(),
+            headers: BTreeMap::new(),
+            timeout_seconds: 5,
+            retries,
+        }
+    }
+
+    fn event(outcome: ScheduleOutcome) -> ScheduleEvent {
+        ScheduleEvent {
+            schedule_id: "hourly_chime".to_string(),
+            planned_at: Local::now(),
+            triggered_at: Local::now(),
+            outcome,
+        }
+    }
+
+    fn client() -> reqwest::Client {
+        reqwest::Client::new()
+    }
+
+    const TEST_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let stand_in = StandIn { failures: 2, ..StandIn::default() };
+        let url = spawn_stand_in(stand_in.clone()).await;
+
+        deliver(&client(), &hook(url, 3), &event(ScheduleOutcome::Fired), TEST_RETRY_DELAY).await.unwrap();
+        assert_eq!(stand_in.requests.load(Ordering::SeqCst), 3);
+        assert_eq!(stand_in.received.lock().unwrap()[0]["schedule_id"], "hourly_chime");
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_retries() {
+        let stand_in = StandIn { failures: usize::MAX, ..StandIn::default() };
+        let url = spawn_stand_in(stand_in.clone()).await;
+
+        let result = deliver(&client(), &hook(url, 2), &event(ScheduleOutcome::Fired), TEST_RETRY_DELAY).await;
+        assert!(result.is_err());
+        // 最初の送信 + 再送2回
+        assert_eq!(stand_in.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_receiver() {
+        let stand_in = StandIn { delay: Duration::from_secs(5), ..StandIn::default() };
+        let url = spawn_stand_in(stand_in.clone()).await;
+        let mut hook = hook(url, 0);
+        hook.timeout_seconds = 1;
+
+        let started = std::time::Instant::now();
+        let result = deliver(&client(), &hook, &event(ScheduleOutcome::Fired), TEST_RETRY_DELAY).await;
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(stand_in.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn publishes_only_selected_events() {
+        let failed_only = StandIn::default();
+        let all = StandIn::default();
+        let mut failed_hook = hook(spawn_stand_in(failed_only.clone()).await, 0);
+        failed_hook.events = vec!["failed".to_string()];
+        let all_hook = hook(spawn_stand_in(all.clone()).await, 0);
+
+        let publisher = WebhookPublisher::new(&[failed_hook, all_hook]).unwrap();
+        publisher.publish(&event(ScheduleOutcome::Succeeded { fallback: None }));
+        publisher.publish(&event(ScheduleOutcome::Failed { error: "Audio file not found".to_string() }));
+
+        // 送信はバックグラウンドで行われるため、全件届くまで待つ
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while all.requests.load(Ordering::SeqCst) < 2 || failed_only.requests.load(Ordering::SeqCst) < 1 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(all.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(failed_only.requests.load(Ordering::SeqCst), 1);
+        assert_eq!(failed_only.received.lock().unwrap()[0]["outcome"], "failed");
+    }
+}